@@ -1,8 +1,21 @@
 pub mod render;
+mod schema;
 
 pub use dox_derive::Dox;
+pub use schema::Schema;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+};
 use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Typ {
@@ -24,7 +37,26 @@ pub enum Typ {
     Char,
     String,
     PathBuf,
-    Vec(Box<Typ>),
+    /// A sequence whose elements are described by the contained field.
+    Vec(Box<Field>),
+    /// A sequence of exactly the given length, e.g. `[u8; 3]`.
+    Array(Box<Field>, usize),
+    /// A sequence of unique elements, e.g. `HashSet<String>`.
+    Set(Box<Field>),
+    /// A map from keys to values, each described by a field.
+    Map(Box<Field>, Box<Field>),
+    /// A fixed-length sequence whose elements are each described by a field,
+    /// e.g. a tuple struct.
+    Tuple(Vec<Field>),
+    /// The unit value, written as null, e.g. a unit struct.
+    Unit,
+    /// A standard library type written in a conventional format.
+    Formatted(Format),
+    /// A reference to a named type documented elsewhere, e.g. in a [`Schema`].
+    Ref {
+        path: String,
+        type_name: String,
+    },
 }
 
 impl fmt::Display for Typ {
@@ -48,24 +80,196 @@ impl fmt::Display for Typ {
             Typ::Char => write!(f, "char"),
             Typ::String => write!(f, "String"),
             Typ::PathBuf => write!(f, "PathBuf"),
-            Typ::Vec(inner) => write!(f, "Vec<{}>", inner),
+            Typ::Vec(inner) => write!(f, "Vec<{}>", inner.type_name()),
+            Typ::Array(inner, len) => write!(f, "[{}; {}]", inner.type_name(), len),
+            Typ::Set(inner) => write!(f, "set of {}", inner.type_name()),
+            Typ::Map(key, value) => {
+                write!(f, "map of {} → {}", key.type_name(), value.type_name())
+            }
+            Typ::Tuple(fields) => {
+                let names: Vec<_> = fields.iter().map(Field::type_name).collect();
+                write!(f, "({})", names.join(", "))
+            }
+            Typ::Unit => write!(f, "()"),
+            Typ::Formatted(format) => write!(f, "{}", format),
+            Typ::Ref { type_name, .. } => write!(f, "{}", type_name),
         }
     }
 }
 
+/// The format of a standard library type, as serde writes it.
 #[derive(Debug, Clone, PartialEq)]
-pub struct Primitive {
+pub enum Format {
+    /// `std::time::Duration`, an object with `secs` and `nanos`.
+    Duration,
+    /// `std::time::SystemTime`, an object with `secs_since_epoch` and `nanos_since_epoch`.
+    SystemTime,
+    /// `std::net::IpAddr`, an IPv4 or IPv6 address string.
+    IpAddr,
+    /// `std::net::Ipv4Addr`, an IPv4 address string.
+    Ipv4Addr,
+    /// `std::net::Ipv6Addr`, an IPv6 address string.
+    Ipv6Addr,
+    /// `std::net::SocketAddr`, an IP address and port string.
+    SocketAddr,
+    /// `std::net::SocketAddrV4`, an IPv4 address and port string.
+    SocketAddrV4,
+    /// `std::net::SocketAddrV6`, an IPv6 address and port string.
+    SocketAddrV6,
+    /// A `std::num::NonZero*` integer of the given type.
+    NonZero(Box<Typ>),
+}
+
+impl Format {
+    /// A short name for the format, e.g. `ipv4`.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Format::Duration => "duration",
+            Format::SystemTime => "system-time",
+            Format::IpAddr => "ip",
+            Format::Ipv4Addr => "ipv4",
+            Format::Ipv6Addr => "ipv6",
+            Format::SocketAddr => "socket-addr",
+            Format::SocketAddrV4 => "socket-addr-v4",
+            Format::SocketAddrV6 => "socket-addr-v6",
+            Format::NonZero(_) => "non-zero",
+        }
+    }
+
+    /// How a value in this format is written, for people filling in a config.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Format::Duration => "an object with `secs` and `nanos`",
+            Format::SystemTime => "an object with `secs_since_epoch` and `nanos_since_epoch`",
+            Format::IpAddr => "an IPv4 or IPv6 address, e.g. \"127.0.0.1\"",
+            Format::Ipv4Addr => "an IPv4 address, e.g. \"127.0.0.1\"",
+            Format::Ipv6Addr => "an IPv6 address, e.g. \"::1\"",
+            Format::SocketAddr => "an IP address and port, e.g. \"127.0.0.1:8080\"",
+            Format::SocketAddrV4 => "an IPv4 address and port, e.g. \"127.0.0.1:8080\"",
+            Format::SocketAddrV6 => "an IPv6 address and port, e.g. \"[::1]:8080\"",
+            Format::NonZero(_) => "an integer other than 0",
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Duration => write!(f, "Duration"),
+            Format::SystemTime => write!(f, "SystemTime"),
+            Format::IpAddr => write!(f, "IpAddr"),
+            Format::Ipv4Addr => write!(f, "Ipv4Addr"),
+            Format::Ipv6Addr => write!(f, "Ipv6Addr"),
+            Format::SocketAddr => write!(f, "SocketAddr"),
+            Format::SocketAddrV4 => write!(f, "SocketAddrV4"),
+            Format::SocketAddrV6 => write!(f, "SocketAddrV6"),
+            Format::NonZero(typ) => write!(f, "NonZero<{}>", typ),
+        }
+    }
+}
+
+/// Whether serde reads a field when deserializing, writes it when serializing,
+/// or both.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Direction {
+    #[default]
+    Both,
+    /// `#[serde(skip_serializing)]`: the field is read, but never written.
+    Input,
+    /// `#[serde(skip_deserializing)]`: the field is written, but never read.
+    Output,
+}
+
+/// Where a field's default comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DefaultKind {
+    /// `#[serde(default)]`: the type's `Default` implementation.
+    Trait,
+    /// `#[serde(default = "path")]`: the named function.
+    Path(String),
+}
+
+/// A default that applies when a field is omitted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefaultValue {
+    pub kind: DefaultKind,
+    /// The default, serialized, if the type implements `Serialize`. Unknown
+    /// in a generic type for fields whose type mentions a type parameter, as
+    /// whether it implements `Default` and `Serialize` depends on the parameter.
+    pub value: Option<serde_json::Value>,
+}
+
+impl fmt::Display for DefaultValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.value, &self.kind) {
+            (Some(value), _) => write!(f, "{}", value),
+            (None, DefaultKind::Trait) => write!(f, "Default::default()"),
+            (None, DefaultKind::Path(path)) => write!(f, "{}()", path),
+        }
+    }
+}
+
+/// What every field records about how it appears in its parent, whatever its type.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FieldMeta {
+    /// The name under which the field appears in its parent.
     pub name: String,
-    pub typ: Typ,
     pub doc: String,
+    /// Whether the field may be omitted, e.g. because it is an `Option`.
+    pub optional: bool,
+    /// The type this field was spliced in from by `#[serde(flatten)]`, if any.
+    pub flattened_from: Option<String>,
+    /// Whether the field's keys sit in its parent, by `#[serde(flatten)]`,
+    /// rather than under its name. Set on maps and enums, whose keys are not
+    /// known ahead; a flattened struct's fields are spliced in instead.
+    pub flattened: bool,
+    /// The default used when the field is omitted, if any.
+    pub default: Option<DefaultValue>,
+    /// Whether the field is left out of human-readable documentation.
+    pub hidden: bool,
+    /// An example value, from `#[dox(example = ...)]`.
+    pub example: Option<serde_json::Value>,
+    /// Other names accepted for the field when deserializing, from `#[serde(alias)]`.
+    pub aliases: Vec<String>,
+    /// The name written when serializing, if it differs from `name`.
+    pub serialized_name: Option<String>,
+    /// Whether the field is read, written, or both.
+    pub direction: Direction,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Primitive {
+    pub meta: FieldMeta,
+    pub typ: Typ,
+    /// The name of the type, for a newtype documented as the value it wraps,
+    /// e.g. `Port` for `struct Port(u16)`.
+    pub type_name: Option<String>,
+}
+
+impl Primitive {
+    /// An unnamed, undocumented value of the given type.
+    pub fn new(typ: Typ) -> Self {
+        Primitive {
+            meta: FieldMeta::default(),
+            typ,
+            type_name: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Container {
-    pub name: String,
+    pub meta: FieldMeta,
     pub type_name: String,
+    /// The full path of the type, identifying it uniquely.
+    pub path: String,
+    /// The documentation of the type itself, whereas `meta.doc` documents
+    /// the field that uses it.
+    pub type_doc: String,
     pub fields: Vec<Field>,
-    pub doc: String,
+    /// Whether keys that match none of the fields are rejected, from
+    /// `#[serde(deny_unknown_fields)]`.
+    pub deny_unknown_fields: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -75,17 +279,250 @@ pub enum Field {
     Enum(Enum),
 }
 
+impl Field {
+    /// The name of this field's type, as it should be shown to users.
+    pub fn type_name(&self) -> String {
+        match self {
+            Field::Primitive(p) => p.type_name.clone().unwrap_or_else(|| p.typ.to_string()),
+            Field::Container(c) => c.type_name.clone(),
+            Field::Enum(e) => e.type_name.clone(),
+        }
+    }
+
+    /// What this field records about how it appears in its parent.
+    pub fn meta(&self) -> &FieldMeta {
+        match self {
+            Field::Primitive(p) => &p.meta,
+            Field::Container(c) => &c.meta,
+            Field::Enum(e) => &e.meta,
+        }
+    }
+
+    /// Mutable access to what this field records about how it appears in its parent.
+    pub fn meta_mut(&mut self) -> &mut FieldMeta {
+        match self {
+            Field::Primitive(p) => &mut p.meta,
+            Field::Container(c) => &mut c.meta,
+            Field::Enum(e) => &mut e.meta,
+        }
+    }
+
+    /// The name under which this field appears in its parent.
+    pub fn name(&self) -> &str {
+        &self.meta().name
+    }
+
+    /// Sets the name under which this field appears in its parent.
+    pub fn set_name(&mut self, name: &str) {
+        self.meta_mut().name = name.to_string();
+    }
+
+    /// The documentation for this field.
+    pub fn doc(&self) -> &str {
+        &self.meta().doc
+    }
+
+    /// Sets the documentation for this field.
+    pub fn set_doc(&mut self, doc: &str) {
+        self.meta_mut().doc = doc.to_string();
+    }
+
+    /// Whether this field may be omitted.
+    pub fn is_optional(&self) -> bool {
+        self.meta().optional
+    }
+
+    /// Marks this field as one that may be omitted.
+    pub fn set_optional(&mut self, optional: bool) {
+        self.meta_mut().optional = optional;
+    }
+
+    /// The default used when this field is omitted, if any.
+    pub fn default(&self) -> Option<&DefaultValue> {
+        self.meta().default.as_ref()
+    }
+
+    /// Sets the default used when this field is omitted.
+    pub fn set_default(&mut self, default: Option<DefaultValue>) {
+        self.meta_mut().default = default;
+    }
+
+    /// Whether this field is left out of human-readable documentation.
+    pub fn is_hidden(&self) -> bool {
+        self.meta().hidden
+    }
+
+    /// Marks this field as one to leave out of human-readable documentation.
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.meta_mut().hidden = hidden;
+    }
+
+    /// An example value for this field, if one was given.
+    pub fn example(&self) -> Option<&serde_json::Value> {
+        self.meta().example.as_ref()
+    }
+
+    /// Sets an example value for this field.
+    pub fn set_example(&mut self, example: Option<serde_json::Value>) {
+        self.meta_mut().example = example;
+    }
+
+    /// Other names accepted for this field when deserializing.
+    pub fn aliases(&self) -> &[String] {
+        &self.meta().aliases
+    }
+
+    /// Sets the other names accepted for this field when deserializing.
+    pub fn set_aliases(&mut self, aliases: Vec<String>) {
+        self.meta_mut().aliases = aliases;
+    }
+
+    /// The name written when serializing, if it differs from the name read.
+    pub fn serialized_name(&self) -> Option<&str> {
+        self.meta().serialized_name.as_deref()
+    }
+
+    /// Sets the name written when serializing, if it differs from the name read.
+    pub fn set_serialized_name(&mut self, name: Option<String>) {
+        self.meta_mut().serialized_name = name;
+    }
+
+    /// Whether this field is read, written, or both.
+    pub fn direction(&self) -> Direction {
+        self.meta().direction
+    }
+
+    /// Sets whether this field is read, written, or both.
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.meta_mut().direction = direction;
+    }
+
+    /// The type this field was spliced in from by `#[serde(flatten)]`, if any.
+    pub fn flattened_from(&self) -> Option<&str> {
+        self.meta().flattened_from.as_deref()
+    }
+
+    /// Records the type this field was spliced in from by `#[serde(flatten)]`.
+    pub fn set_flattened_from(&mut self, source: Option<String>) {
+        self.meta_mut().flattened_from = source;
+    }
+
+    /// Whether the field's keys sit in its parent rather than under its name.
+    pub fn is_flattened(&self) -> bool {
+        self.meta().flattened
+    }
+
+    /// The fields this field contributes to its parent when marked
+    /// `#[serde(flatten)]`. A container's fields are spliced in, recording the
+    /// container's type as their origin. Maps and enums are kept, marked as
+    /// flattened: a map's entries are any other keys of the parent, and an
+    /// enum's keys are those of one of its variants.
+    pub fn into_flattened(self) -> Vec<Field> {
+        match self {
+            Field::Container(container) => container
+                .fields
+                .into_iter()
+                .map(|mut field| {
+                    if field.flattened_from().is_none() {
+                        field.set_flattened_from(Some(container.type_name.clone()));
+                    }
+                    if container.meta.optional {
+                        field.set_optional(true);
+                    }
+                    field
+                })
+                .collect(),
+            mut field @ (Field::Enum(_)
+            | Field::Primitive(Primitive {
+                typ: Typ::Map(..), ..
+            })) => {
+                field.meta_mut().flattened = true;
+                vec![field]
+            }
+            field => vec![field],
+        }
+    }
+
+    /// The containers and enums this field documents, looking through
+    /// collection types to their elements.
+    pub fn nested(&self) -> Vec<&Field> {
+        match self {
+            Field::Primitive(p) => match &p.typ {
+                Typ::Vec(inner) | Typ::Array(inner, _) | Typ::Set(inner) => inner.nested(),
+                Typ::Map(key, value) => {
+                    let mut nested = key.nested();
+                    nested.extend(value.nested());
+                    nested
+                }
+                Typ::Tuple(fields) => fields.iter().flat_map(Field::nested).collect(),
+                _ => vec![],
+            },
+            Field::Container(_) | Field::Enum(_) => vec![self],
+        }
+    }
+}
+
+/// The payload carried by an enum variant.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VariantFields {
+    /// A variant without data, e.g. `Plain`.
+    Unit,
+    /// A tuple variant, e.g. `Compound(Vec<String>)`.
+    Unnamed(Vec<Field>),
+    /// A struct variant, e.g. `Listen { host: String, port: u16 }`.
+    Named(Vec<Field>),
+}
+
+impl VariantFields {
+    /// The fields of the payload, in declaration order.
+    pub fn fields(&self) -> &[Field] {
+        match self {
+            VariantFields::Unit => &[],
+            VariantFields::Unnamed(fields) | VariantFields::Named(fields) => fields,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Variant {
     pub name: String,
     pub doc: String,
+    pub fields: VariantFields,
+    /// Whether the variant is left out of human-readable documentation.
+    pub hidden: bool,
+    /// Other names accepted for the variant when deserializing, from `#[serde(alias)]`.
+    pub aliases: Vec<String>,
+    /// The name written when serializing, if it differs from `name`.
+    pub serialized_name: Option<String>,
+    /// Whether unknown variants deserialize to this one, from `#[serde(other)]`.
+    pub other: bool,
+}
+
+/// How an enum is written when serialized, following serde's enum representations.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Representation {
+    /// The default: a variant's name, or an object with the name as its only key.
+    External,
+    /// `#[serde(tag = "...")]`: an object whose `tag` field names the variant.
+    Internal { tag: String },
+    /// `#[serde(tag = "...", content = "...")]`: an object with the variant name
+    /// under `tag` and its data under `content`.
+    Adjacent { tag: String, content: String },
+    /// `#[serde(untagged)]`: the variant's data alone, with no name.
+    Untagged,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Enum {
-    pub name: String,
-    pub doc: String,
+    pub meta: FieldMeta,
+    pub type_name: String,
+    /// The full path of the type, identifying it uniquely.
+    pub path: String,
+    /// The documentation of the type itself, whereas `meta.doc` documents
+    /// the field that uses it.
+    pub type_doc: String,
     pub variants: Vec<Variant>,
+    pub representation: Representation,
 }
 
 pub trait Dox {
@@ -94,16 +531,114 @@ pub trait Dox {
 
 impl<T: Dox> Dox for Vec<T> {
     fn dox() -> Field {
-        let inner = T::dox();
-        let inner_type = match inner {
-            Field::Primitive(p) => p.typ,
-            Field::Container(_) | Field::Enum(_) => Typ::String, // Use String as a placeholder for custom types
-        };
-        Field::Primitive(Primitive {
-            name: String::new(),
-            typ: Typ::Vec(Box::new(inner_type)),
-            doc: String::new(),
-        })
+        Field::Primitive(Primitive::new(Typ::Vec(Box::new(T::dox()))))
+    }
+}
+
+impl<T: Dox, const N: usize> Dox for [T; N] {
+    fn dox() -> Field {
+        Field::Primitive(Primitive::new(Typ::Array(Box::new(T::dox()), N)))
+    }
+}
+
+fn set_dox<T: Dox>() -> Field {
+    Field::Primitive(Primitive::new(Typ::Set(Box::new(T::dox()))))
+}
+
+impl<T: Dox, S> Dox for HashSet<T, S> {
+    fn dox() -> Field {
+        set_dox::<T>()
+    }
+}
+
+impl<T: Dox> Dox for BTreeSet<T> {
+    fn dox() -> Field {
+        set_dox::<T>()
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<T: Dox, S> Dox for indexmap::IndexSet<T, S> {
+    fn dox() -> Field {
+        set_dox::<T>()
+    }
+}
+
+fn map_dox<K: Dox, V: Dox>() -> Field {
+    Field::Primitive(Primitive::new(Typ::Map(
+        Box::new(K::dox()),
+        Box::new(V::dox()),
+    )))
+}
+
+impl<K: Dox, V: Dox, S> Dox for HashMap<K, V, S> {
+    fn dox() -> Field {
+        map_dox::<K, V>()
+    }
+}
+
+impl<K: Dox, V: Dox> Dox for BTreeMap<K, V> {
+    fn dox() -> Field {
+        map_dox::<K, V>()
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K: Dox, V: Dox, S> Dox for indexmap::IndexMap<K, V, S> {
+    fn dox() -> Field {
+        map_dox::<K, V>()
+    }
+}
+
+impl<T: Dox> Dox for Option<T> {
+    fn dox() -> Field {
+        let mut field = T::dox();
+        field.set_optional(true);
+        field
+    }
+}
+
+// Wrappers that serde serializes as the value they hold.
+macro_rules! impl_dox_for_wrapper {
+    ($($t:ident),*) => {
+        $(
+            impl<T: Dox + ?Sized> Dox for $t<T> {
+                fn dox() -> Field {
+                    T::dox()
+                }
+            }
+        )*
+    }
+}
+
+impl_dox_for_wrapper!(Box, Rc, Arc, Cell, RefCell, Mutex, RwLock);
+
+impl<B: Dox + ToOwned + ?Sized> Dox for Cow<'_, B> {
+    fn dox() -> Field {
+        B::dox()
+    }
+}
+
+macro_rules! impl_dox_for_tuple {
+    ($(($($t:ident),+)),*) => {
+        $(
+            impl<$($t: Dox),+> Dox for ($($t,)+) {
+                fn dox() -> Field {
+                    Field::Primitive(Primitive::new(Typ::Tuple(vec![$($t::dox()),+])))
+                }
+            }
+        )*
+    }
+}
+
+impl_dox_for_tuple! {
+    (A), (A, B), (A, B, C), (A, B, C, D), (A, B, C, D, E), (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G), (A, B, C, D, E, F, G, H)
+}
+
+impl Dox for () {
+    fn dox() -> Field {
+        Field::Primitive(Primitive::new(Typ::Unit))
     }
 }
 
@@ -112,11 +647,7 @@ macro_rules! impl_dox_for_primitive {
         $(
             impl Dox for $t {
                 fn dox() -> Field {
-                    Field::Primitive(Primitive {
-                        name: String::new(),
-                        typ: Typ::$variant,
-                        doc: String::new(),
-                    })
+                    Field::Primitive(Primitive::new(Typ::$variant))
                 }
             }
         )*
@@ -130,28 +661,194 @@ impl_dox_for_primitive! {
     bool => Bool, char => Char
 }
 
+macro_rules! impl_dox_for_format {
+    ($($t:ty => $format:expr),*) => {
+        $(
+            impl Dox for $t {
+                fn dox() -> Field {
+                    Field::Primitive(Primitive::new(Typ::Formatted($format)))
+                }
+            }
+        )*
+    }
+}
+
+impl_dox_for_format! {
+    Duration => Format::Duration,
+    SystemTime => Format::SystemTime,
+    IpAddr => Format::IpAddr,
+    Ipv4Addr => Format::Ipv4Addr,
+    Ipv6Addr => Format::Ipv6Addr,
+    SocketAddr => Format::SocketAddr,
+    SocketAddrV4 => Format::SocketAddrV4,
+    SocketAddrV6 => Format::SocketAddrV6,
+    NonZeroI8 => Format::NonZero(Box::new(Typ::I8)),
+    NonZeroI16 => Format::NonZero(Box::new(Typ::I16)),
+    NonZeroI32 => Format::NonZero(Box::new(Typ::I32)),
+    NonZeroI64 => Format::NonZero(Box::new(Typ::I64)),
+    NonZeroI128 => Format::NonZero(Box::new(Typ::I128)),
+    NonZeroIsize => Format::NonZero(Box::new(Typ::Isize)),
+    NonZeroU8 => Format::NonZero(Box::new(Typ::U8)),
+    NonZeroU16 => Format::NonZero(Box::new(Typ::U16)),
+    NonZeroU32 => Format::NonZero(Box::new(Typ::U32)),
+    NonZeroU64 => Format::NonZero(Box::new(Typ::U64)),
+    NonZeroU128 => Format::NonZero(Box::new(Typ::U128)),
+    NonZeroUsize => Format::NonZero(Box::new(Typ::Usize))
+}
+
 impl Dox for String {
     fn dox() -> Field {
-        Field::Primitive(Primitive {
-            name: String::new(),
-            typ: Typ::String,
-            doc: String::new(),
-        })
+        Field::Primitive(Primitive::new(Typ::String))
+    }
+}
+
+impl Dox for str {
+    fn dox() -> Field {
+        String::dox()
+    }
+}
+
+impl<T: Dox> Dox for [T] {
+    fn dox() -> Field {
+        Vec::<T>::dox()
     }
 }
 
 impl Dox for PathBuf {
     fn dox() -> Field {
-        Field::Primitive(Primitive {
-            name: String::new(),
-            typ: Typ::PathBuf,
-            doc: String::new(),
-        })
+        Field::Primitive(Primitive::new(Typ::PathBuf))
+    }
+}
+
+#[doc(hidden)]
+pub mod __private {
+    //! Support for code generated by `#[derive(Dox)]`.
+
+    use std::cell::RefCell;
+    use std::marker::PhantomData;
+
+    pub use serde_json::Value;
+
+    use crate::{Field, Primitive, Typ};
+
+    thread_local! {
+        /// The paths of the types whose `dox()` is running on this thread, and
+        /// whether each has been referred to while it is built.
+        static BUILDING: RefCell<Vec<(&'static str, bool)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Pops a type from `BUILDING` when its `dox()` returns or unwinds.
+    struct Building;
+
+    impl Drop for Building {
+        fn drop(&mut self) {
+            BUILDING.with(|building| building.borrow_mut().pop());
+        }
+    }
+
+    /// Builds a type's field tree, unless the type is already being built
+    /// further up the stack. A recursive type then refers to itself with a
+    /// `Typ::Ref` rather than expanding forever.
+    ///
+    /// # Panics
+    ///
+    /// If a type that is written as a single value, such as a newtype, refers
+    /// to itself through other types: it has no definition for the reference
+    /// to point to. Direct recursion is rejected by the derive.
+    pub fn build_once(path: &'static str, type_name: &str, build: impl FnOnce() -> Field) -> Field {
+        let recursive = BUILDING.with(|building| {
+            let mut building = building.borrow_mut();
+            if let Some((_, referred)) = building.iter_mut().find(|(p, _)| *p == path) {
+                *referred = true;
+                return true;
+            }
+            building.push((path, false));
+            false
+        });
+        if recursive {
+            return Field::Primitive(Primitive::new(Typ::Ref {
+                path: path.to_string(),
+                type_name: type_name.to_string(),
+            }));
+        }
+        let _building = Building;
+        let field = build();
+        let referred = BUILDING.with(|building| building.borrow().last().is_some_and(|b| b.1));
+        if referred && matches!(field, Field::Primitive(_)) {
+            panic!(
+                "dox: `{}` contains itself, but is written as a single value and so has no \
+                 definition to refer to; give it named fields",
+                type_name
+            );
+        }
+        field
+    }
+
+    /// Wraps a default value so that it is serialized only if its type
+    /// implements `Serialize`, via autoref specialization.
+    pub struct DefaultProbe<'a, T>(pub &'a T);
+
+    pub trait SerializeDefault {
+        fn default_value(&self) -> Option<Value>;
+    }
+
+    impl<T: serde::Serialize> SerializeDefault for DefaultProbe<'_, T> {
+        fn default_value(&self) -> Option<Value> {
+            serde_json::to_value(self.0).ok()
+        }
+    }
+
+    pub trait OpaqueDefault {
+        fn default_value(&self) -> Option<Value>;
+    }
+
+    impl<T> OpaqueDefault for &DefaultProbe<'_, T> {
+        fn default_value(&self) -> Option<Value> {
+            None
+        }
+    }
+
+    /// Calls a function with a type's default instance only if the type
+    /// implements `Default`, via autoref specialization. In a generic impl, a
+    /// type that mentions a type parameter never does, so its default is not
+    /// known there.
+    pub struct InstanceProbe<T, F>(F, PhantomData<T>);
+
+    impl<T, F> InstanceProbe<T, F> {
+        pub fn new(with_default: F) -> Self {
+            InstanceProbe(with_default, PhantomData)
+        }
+    }
+
+    pub trait DefaultInstance {
+        fn default_value(&self) -> Option<Value>;
+    }
+
+    impl<T: Default, F: Fn(T) -> Option<Value>> DefaultInstance for InstanceProbe<T, F> {
+        fn default_value(&self) -> Option<Value> {
+            (self.0)(T::default())
+        }
+    }
+
+    pub trait OpaqueInstance {
+        fn default_value(&self) -> Option<Value>;
+    }
+
+    impl<T, F> OpaqueInstance for &InstanceProbe<T, F> {
+        fn default_value(&self) -> Option<Value> {
+            None
+        }
     }
 }
 
 pub trait Renderer {
     fn render(&self, doc_type: Field) -> String;
+
+    /// Renders a schema. Renderers that document each type once override this;
+    /// by default, the root is rendered with its types expanded in place.
+    fn render_schema(&self, schema: &Schema) -> String {
+        self.render(schema.expand(&schema.root))
+    }
 }
 
 pub fn render<T: Dox, R: Renderer>(renderer: &R) -> String {
@@ -0,0 +1,581 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+
+use super::{flattened, is_input, shown_name, Flattened};
+use crate::{
+    Container, Direction, Enum, Field, Format, Renderer, Representation, Typ, Variant,
+    VariantFields,
+};
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Renders a JSON Schema (draft 2020-12) document describing the type, for
+/// validation and completion in editors.
+pub struct JsonSchema;
+
+impl Renderer for JsonSchema {
+    fn render(&self, doc_type: Field) -> String {
+        serde_json::to_string_pretty(&to_json_schema(&doc_type)).unwrap()
+    }
+}
+
+/// Converts a field tree into a JSON Schema (draft 2020-12) document. Named
+/// types are collected into `$defs` and referred to with `$ref`.
+pub fn to_json_schema(field: &Field) -> Value {
+    let mut builder = Builder {
+        root: match field {
+            Field::Container(container) => Some(container.path.clone()),
+            Field::Enum(enum_type) => Some(enum_type.path.clone()),
+            Field::Primitive(_) => None,
+        },
+        defs: Map::new(),
+        names: HashMap::new(),
+    };
+    let mut schema = match field {
+        Field::Container(container) => builder.container(container),
+        Field::Enum(enum_type) => builder.enumeration(enum_type),
+        Field::Primitive(_) => builder.field(field),
+    };
+    let object = schema.as_object_mut().unwrap();
+    object.insert("$schema".to_string(), json!(DRAFT));
+    object.insert("title".to_string(), json!(field.type_name()));
+    if !builder.defs.is_empty() {
+        object.insert("$defs".to_string(), Value::Object(builder.defs));
+    }
+    schema
+}
+
+struct Builder {
+    /// The path of the root type, which references refer to as `#`.
+    root: Option<String>,
+    defs: Map<String, Value>,
+    /// The name in `$defs` of each type, by path.
+    names: HashMap<String, String>,
+}
+
+impl Builder {
+    /// The schema for a field, including its documentation and default.
+    fn field(&mut self, field: &Field) -> Value {
+        let mut schema = match field {
+            Field::Primitive(prim) => self.typ(&prim.typ),
+            Field::Container(container) => {
+                self.define(&container.path, &container.type_name, field)
+            }
+            Field::Enum(enum_type) => self.define(&enum_type.path, &enum_type.type_name, field),
+        };
+        // An `Option` may also be given as null.
+        if field.is_optional() {
+            schema = json!({ "anyOf": [schema, { "type": "null" }] });
+        }
+        let doc = &field.meta().doc;
+        let object = schema.as_object_mut().unwrap();
+        if !doc.is_empty() {
+            object.insert("description".to_string(), json!(doc));
+        }
+        if let Some(value) = field.default().and_then(|d| d.value.clone()) {
+            object.insert("default".to_string(), value);
+        }
+        if let Some(example) = field.example() {
+            object.insert("examples".to_string(), json!([example]));
+        }
+        match field.direction() {
+            Direction::Both => {}
+            Direction::Input => {
+                object.insert("writeOnly".to_string(), json!(true));
+            }
+            Direction::Output => {
+                object.insert("readOnly".to_string(), json!(true));
+            }
+        }
+        schema
+    }
+
+    /// Adds a named type to `$defs`, returning a reference to it.
+    fn define(&mut self, path: &str, type_name: &str, field: &Field) -> Value {
+        let name = self.name(path, type_name);
+        if !self.defs.contains_key(&name) {
+            // Reserve the name first, so the definition is only built once.
+            self.defs.insert(name.clone(), Value::Null);
+            let schema = match field {
+                Field::Container(container) => self.container(container),
+                Field::Enum(enum_type) => self.enumeration(enum_type),
+                Field::Primitive(_) => unreachable!(),
+            };
+            self.defs.insert(name.clone(), schema);
+        }
+        json!({ "$ref": format!("#/$defs/{}", name) })
+    }
+
+    /// The name in `$defs` of the type at `path`. Names are made of the
+    /// characters that need no escaping in a URI fragment, and types that
+    /// share a name, such as `a::Config` and `b::Config`, are told apart by
+    /// their paths.
+    fn name(&mut self, path: &str, type_name: &str) -> String {
+        if let Some(name) = self.names.get(path) {
+            return name.clone();
+        }
+        let taken = |name: &String| self.names.values().any(|n| n == name);
+        let mut name = def_name(type_name);
+        if taken(&name) {
+            name = def_name(path);
+        }
+        let base = name.clone();
+        let mut suffix = 2;
+        while taken(&name) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        self.names.insert(path.to_string(), name.clone());
+        name
+    }
+
+    fn typ(&mut self, typ: &Typ) -> Value {
+        match typ {
+            Typ::I8 => json!({ "type": "integer", "minimum": i8::MIN, "maximum": i8::MAX }),
+            Typ::I16 => json!({ "type": "integer", "minimum": i16::MIN, "maximum": i16::MAX }),
+            Typ::I32 => json!({ "type": "integer", "minimum": i32::MIN, "maximum": i32::MAX }),
+            Typ::U8 => json!({ "type": "integer", "minimum": 0, "maximum": u8::MAX }),
+            Typ::U16 => json!({ "type": "integer", "minimum": 0, "maximum": u16::MAX }),
+            Typ::U32 => json!({ "type": "integer", "minimum": 0, "maximum": u32::MAX }),
+            Typ::I64 | Typ::I128 | Typ::Isize => json!({ "type": "integer" }),
+            Typ::U64 | Typ::U128 | Typ::Usize => json!({ "type": "integer", "minimum": 0 }),
+            Typ::F32 | Typ::F64 => json!({ "type": "number" }),
+            Typ::Bool => json!({ "type": "boolean" }),
+            Typ::Char => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+            Typ::String | Typ::PathBuf => json!({ "type": "string" }),
+            Typ::Vec(inner) => json!({ "type": "array", "items": self.field(inner) }),
+            Typ::Array(inner, len) => json!({
+                "type": "array",
+                "items": self.field(inner),
+                "minItems": len,
+                "maxItems": len,
+            }),
+            Typ::Set(inner) => json!({
+                "type": "array",
+                "items": self.field(inner),
+                "uniqueItems": true,
+            }),
+            Typ::Map(_, value) => {
+                json!({ "type": "object", "additionalProperties": self.field(value) })
+            }
+            Typ::Tuple(fields) => self.tuple(fields),
+            Typ::Unit => json!({ "type": "null" }),
+            Typ::Formatted(Format::Duration) => json!({
+                "type": "object",
+                "properties": {
+                    "secs": { "type": "integer", "minimum": 0 },
+                    "nanos": { "type": "integer", "minimum": 0, "maximum": 999_999_999 },
+                },
+                "required": ["secs", "nanos"],
+            }),
+            Typ::Formatted(Format::SystemTime) => json!({
+                "type": "object",
+                "properties": {
+                    "secs_since_epoch": { "type": "integer", "minimum": 0 },
+                    "nanos_since_epoch": { "type": "integer", "minimum": 0, "maximum": 999_999_999 },
+                },
+                "required": ["secs_since_epoch", "nanos_since_epoch"],
+            }),
+            Typ::Formatted(Format::IpAddr) => json!({
+                "type": "string",
+                "anyOf": [{ "format": "ipv4" }, { "format": "ipv6" }],
+            }),
+            Typ::Formatted(Format::NonZero(typ)) => {
+                let mut schema = self.typ(typ);
+                schema["not"] = json!({ "const": 0 });
+                schema
+            }
+            Typ::Formatted(format) => json!({ "type": "string", "format": format.tag() }),
+            Typ::Ref { path, .. } if self.root.as_ref() == Some(path) => json!({ "$ref": "#" }),
+            Typ::Ref { path, type_name } => {
+                json!({ "$ref": format!("#/$defs/{}", self.name(path, type_name)) })
+            }
+        }
+    }
+
+    /// An array schema for a fixed sequence of positional fields.
+    fn tuple(&mut self, fields: &[Field]) -> Value {
+        let items: Vec<_> = fields.iter().map(|f| self.field(f)).collect();
+        json!({
+            "type": "array",
+            "prefixItems": items,
+            "minItems": fields.len(),
+            "maxItems": fields.len(),
+        })
+    }
+
+    /// An object schema for a set of named fields.
+    fn object(&mut self, fields: &[Field]) -> Map<String, Value> {
+        let mut properties = Map::new();
+        let mut required = vec![];
+        // Constraints that all hold: required fields that have aliases may be given
+        // under any of their names, and flattened enums add the keys of a variant.
+        let mut all_of = vec![];
+        let mut additional = None;
+        for field in fields {
+            match (flattened(field), field) {
+                (Some(Flattened::Map(value)), _) => {
+                    additional = Some(self.field(value));
+                    continue;
+                }
+                (Some(Flattened::Enum(_)), Field::Enum(enum_type)) => {
+                    // An optional flattened enum may be left out, so it adds
+                    // no constraint.
+                    if !field.is_optional() {
+                        all_of.push(self.flattened_enum(enum_type));
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            let name = shown_name(field);
+            let schema = self.field(field);
+            for alias in field.aliases() {
+                properties.insert(alias.clone(), schema.clone());
+            }
+            properties.insert(name.to_string(), schema);
+            // Fields that are only written need not be given.
+            if field.is_optional() || field.default().is_some() || !is_input(field) {
+                continue;
+            }
+            if field.aliases().is_empty() {
+                required.push(json!(name));
+            } else {
+                let names = std::iter::once(name).chain(field.aliases().iter().map(String::as_str));
+                let any: Vec<_> = names.map(|n| json!({ "required": [n] })).collect();
+                all_of.push(json!({ "anyOf": any }));
+            }
+        }
+        let mut object = Map::new();
+        object.insert("type".to_string(), json!("object"));
+        object.insert("properties".to_string(), Value::Object(properties));
+        if !required.is_empty() {
+            object.insert("required".to_string(), Value::Array(required));
+        }
+        if !all_of.is_empty() {
+            object.insert("allOf".to_string(), Value::Array(all_of));
+        }
+        // A flattened map takes the keys that nothing else does, including the
+        // keys of flattened enums' variants.
+        if let Some(additional) = additional {
+            object.insert("unevaluatedProperties".to_string(), additional);
+        }
+        object
+    }
+
+    fn container(&mut self, container: &Container) -> Value {
+        let mut object = self.object(&container.fields);
+        if !container.type_doc.is_empty() {
+            object.insert("description".to_string(), json!(container.type_doc));
+        }
+        if container.deny_unknown_fields {
+            object.insert("additionalProperties".to_string(), json!(false));
+        }
+        Value::Object(object)
+    }
+
+    /// The schema for a variant's data, or `None` for a unit variant.
+    fn payload(&mut self, variant: &Variant) -> Option<Value> {
+        match &variant.fields {
+            VariantFields::Unit => None,
+            VariantFields::Unnamed(fields) if fields.len() == 1 => Some(self.field(&fields[0])),
+            VariantFields::Unnamed(fields) => Some(self.tuple(fields)),
+            VariantFields::Named(fields) => Some(Value::Object(self.object(fields))),
+        }
+    }
+
+    fn variant(&mut self, enum_type: &Enum, variant: &Variant) -> Value {
+        let representation = &enum_type.representation;
+        let name = json!(variant.name);
+        // The schema for the name that selects the variant: a catch-all accepts
+        // any name the other variants do not, and aliases are accepted
+        // alongside the name.
+        let name_schema = if variant.other {
+            let known: Vec<_> = enum_type
+                .variants
+                .iter()
+                .filter(|v| !v.other)
+                .flat_map(|v| std::iter::once(&v.name).chain(&v.aliases))
+                .collect();
+            json!({ "type": "string", "not": { "enum": known } })
+        } else if variant.aliases.is_empty() {
+            json!({ "const": name })
+        } else {
+            let names: Vec<_> = std::iter::once(&variant.name)
+                .chain(&variant.aliases)
+                .collect();
+            json!({ "enum": names })
+        };
+        let mut schema = match self.strict_internal(representation, variant, &name_schema) {
+            Some(schema) => schema,
+            None => self.tagged(representation, variant, name_schema),
+        };
+        if !variant.doc.is_empty() {
+            schema["description"] = json!(variant.doc);
+        }
+        schema
+    }
+
+    /// The schema for a variant of an internally tagged enum whose data is a
+    /// struct that rejects unknown keys. Referring to the struct would reject
+    /// the tag, so the struct is written out with the tag among its keys.
+    fn strict_internal(
+        &mut self,
+        representation: &Representation,
+        variant: &Variant,
+        name_schema: &Value,
+    ) -> Option<Value> {
+        let Representation::Internal { tag } = representation else {
+            return None;
+        };
+        let VariantFields::Unnamed(fields) = &variant.fields else {
+            return None;
+        };
+        let [Field::Container(container)] = fields.as_slice() else {
+            return None;
+        };
+        if !container.deny_unknown_fields {
+            return None;
+        }
+        let mut schema = self.container(container);
+        schema["properties"][tag] = name_schema.clone();
+        let object = schema.as_object_mut().unwrap();
+        let required = object.entry("required").or_insert_with(|| json!([]));
+        required.as_array_mut().unwrap().insert(0, json!(tag));
+        Some(schema)
+    }
+
+    /// The schema for a variant, tagged as the enum's representation says.
+    fn tagged(
+        &mut self,
+        representation: &Representation,
+        variant: &Variant,
+        name_schema: Value,
+    ) -> Value {
+        let name = json!(variant.name);
+        let payload = self.payload(variant);
+        match (representation, payload) {
+            (Representation::External, None) => name_schema,
+            (Representation::External, Some(payload))
+                if variant.other || !variant.aliases.is_empty() =>
+            {
+                json!({
+                    "type": "object",
+                    "propertyNames": name_schema,
+                    "additionalProperties": payload,
+                    "minProperties": 1,
+                    "maxProperties": 1,
+                })
+            }
+            (Representation::External, Some(payload)) => json!({
+                "type": "object",
+                "properties": { variant.name.clone(): payload },
+                "required": [name],
+                "additionalProperties": false,
+            }),
+            (Representation::Internal { tag }, payload) => {
+                let tag_schema = json!({
+                    "type": "object",
+                    "properties": { tag.clone(): name_schema },
+                    "required": [tag],
+                });
+                match payload {
+                    None => tag_schema,
+                    Some(payload) => json!({ "allOf": [tag_schema, payload] }),
+                }
+            }
+            (Representation::Adjacent { tag, content }, payload) => {
+                let mut schema = json!({
+                    "type": "object",
+                    "properties": { tag.clone(): name_schema },
+                    "required": [tag],
+                });
+                if let Some(payload) = payload {
+                    schema["properties"][content] = payload;
+                    schema["required"] = json!([tag, content]);
+                }
+                schema
+            }
+            (Representation::Untagged, None) => json!({ "type": "null" }),
+            (Representation::Untagged, Some(payload)) => payload,
+        }
+    }
+
+    fn enumeration(&mut self, enum_type: &Enum) -> Value {
+        let variants: Vec<_> = enum_type
+            .variants
+            .iter()
+            .map(|v| self.variant(enum_type, v))
+            .collect();
+        let mut schema = one_of(enum_type, variants);
+        if !enum_type.type_doc.is_empty() {
+            schema["description"] = json!(enum_type.type_doc);
+        }
+        schema
+    }
+
+    /// The schema for an enum flattened into an object. An externally tagged
+    /// variant is a key among the object's others, rather than its only key.
+    fn flattened_enum(&mut self, enum_type: &Enum) -> Value {
+        let variants: Vec<_> = enum_type
+            .variants
+            .iter()
+            .map(|variant| {
+                if enum_type.representation != Representation::External {
+                    return self.variant(enum_type, variant);
+                }
+                let payload = self
+                    .payload(variant)
+                    .unwrap_or_else(|| json!({ "type": "null" }));
+                let names: Vec<_> = std::iter::once(&variant.name)
+                    .chain(&variant.aliases)
+                    .collect();
+                let properties: Map<_, _> = names
+                    .iter()
+                    .map(|name| (name.to_string(), payload.clone()))
+                    .collect();
+                let mut schema = json!({ "properties": properties });
+                if let [name] = names.as_slice() {
+                    schema["required"] = json!([name]);
+                } else {
+                    let any: Vec<_> = names.iter().map(|n| json!({ "required": [n] })).collect();
+                    schema["anyOf"] = json!(any);
+                }
+                if !variant.doc.is_empty() {
+                    schema["description"] = json!(variant.doc);
+                }
+                schema
+            })
+            .collect();
+        one_of(enum_type, variants)
+    }
+}
+
+/// Combines the schemas of an enum's variants, so that a value is one of them.
+fn one_of(enum_type: &Enum, variants: Vec<Value>) -> Value {
+    // Untagged variants may overlap, as serde takes the first that matches.
+    let keyword = match enum_type.representation {
+        Representation::Untagged => "anyOf",
+        _ => "oneOf",
+    };
+    json!({ keyword: variants })
+}
+
+/// Turns a type name or path into a name for `$defs`: `Page<User>` becomes
+/// `Page_User`, and `a::Config` becomes `a.Config`.
+fn def_name(name: &str) -> String {
+    let mut result = String::new();
+    for part in name.split("::") {
+        if !result.is_empty() {
+            result.push('.');
+        }
+        for c in part.chars() {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                result.push(c);
+            } else if !result.is_empty() && !result.ends_with(['_', '.']) {
+                result.push('_');
+            }
+        }
+        while result.ends_with('_') {
+            result.pop();
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::fixtures::{config, variant};
+    use crate::Primitive;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_json_schema() {
+        let mut doc = config();
+        let Field::Container(config) = &mut doc else {
+            unreachable!()
+        };
+        let Field::Primitive(Primitive {
+            typ: Typ::Vec(listener),
+            ..
+        }) = &config.fields[1]
+        else {
+            unreachable!()
+        };
+        let listener = (**listener).clone();
+        let Field::Enum(level) = &mut config.fields[2] else {
+            unreachable!()
+        };
+        level.variants.push(variant(
+            "custom",
+            "",
+            VariantFields::Unnamed(vec![listener]),
+        ));
+
+        let expected = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Config",
+            "description": "The service config",
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "description": "The service name" },
+                "listeners": {
+                    "type": "array",
+                    "items": {
+                        "$ref": "#/$defs/Listener",
+                        "description": "A listening socket",
+                    },
+                    "description": "Sockets to listen on",
+                },
+                "level": { "$ref": "#/$defs/Level", "description": "Log level" },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Tags to attach",
+                },
+            },
+            "required": ["name", "listeners", "level", "tags"],
+            "$defs": {
+                "Listener": {
+                    "type": "object",
+                    "description": "A listening socket",
+                    "properties": {
+                        "host": {
+                            "anyOf": [{ "type": "string" }, { "type": "null" }],
+                            "description": "The host to bind",
+                        },
+                        "port": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 65535,
+                            "description": "The port to bind",
+                            "default": 8080,
+                        },
+                    },
+                },
+                "Level": {
+                    "description": "Log level",
+                    "oneOf": [
+                        { "const": "error", "description": "Only errors" },
+                        { "const": "debug", "description": "Everything" },
+                        {
+                            "type": "object",
+                            "properties": {
+                                "custom": {
+                                    "$ref": "#/$defs/Listener",
+                                    "description": "A listening socket",
+                                },
+                            },
+                            "required": ["custom"],
+                            "additionalProperties": false,
+                        },
+                    ],
+                },
+            },
+        });
+
+        assert_eq!(to_json_schema(&doc), expected);
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    also_accepted, describe_catch_all, describe_representation, flattened, format, render_sections,
+    shown_name, visible, visible_variants, written_name, Flattened,
+};
+use crate::{Container, Direction, Enum, Field, Renderer, Schema, Typ, VariantFields};
+
+/// Renders documentation as Markdown, with a section per type, a table of
+/// fields, and links from fields to the sections of their nested types.
+pub struct Markdown;
+
+impl Renderer for Markdown {
+    fn render(&self, doc_type: Field) -> String {
+        self.render_schema(&Schema::new(doc_type))
+    }
+
+    fn render_schema(&self, schema: &Schema) -> String {
+        let titles = titles(schema);
+        render_sections(schema, |field, _| match field {
+            Field::Container(container) => render_container(&titles, container),
+            Field::Enum(enum_type) => render_enum(&titles, enum_type),
+            Field::Primitive(_) => unreachable!(),
+        })
+    }
+}
+
+/// The heading of each type's section, by path.
+type Titles = HashMap<String, String>;
+
+/// Heads each type's section with its name, or with its path where a type of
+/// the same name came first, such as `b::Settings` after `a::Settings`, so
+/// that every section has an anchor of its own.
+fn titles(schema: &Schema) -> Titles {
+    let mut titles = HashMap::new();
+    let mut names = HashSet::new();
+    for definition in &schema.definitions {
+        let (path, type_name) = match definition {
+            Field::Container(container) => (&container.path, &container.type_name),
+            Field::Enum(enum_type) => (&enum_type.path, &enum_type.type_name),
+            Field::Primitive(_) => continue,
+        };
+        let title = if names.insert(type_name) {
+            type_name
+        } else {
+            path
+        };
+        titles.insert(path.clone(), title.clone());
+    }
+    titles
+}
+
+/// The heading of the section for the type at `path`.
+fn title<'a>(titles: &'a Titles, path: &str, type_name: &'a str) -> &'a str {
+    titles.get(path).map_or(type_name, String::as_str)
+}
+
+/// Escapes the characters of a type name that Markdown would take for HTML.
+fn escape(text: &str) -> String {
+    text.replace('<', "\\<").replace('>', "\\>")
+}
+
+/// Computes the anchor GitHub-flavoured Markdown assigns to a heading, from
+/// its text as shown rather than as escaped.
+fn anchor(heading: &str) -> String {
+    heading
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => c.to_lowercase().next(),
+            _ => None,
+        })
+        .collect()
+}
+
+fn link(title: &str) -> String {
+    format!("[`{}`](#{})", title, anchor(title))
+}
+
+/// The type of a field, with links to the sections of any nested types.
+fn type_link(titles: &Titles, field: &Field) -> String {
+    match field {
+        Field::Primitive(prim) if prim.type_name.is_some() => format!("`{}`", field.type_name()),
+        Field::Primitive(prim) => match &prim.typ {
+            Typ::Vec(inner) => format!("Vec\\<{}\\>", type_link(titles, inner)),
+            Typ::Array(inner, len) => {
+                format!("\\[{}; {}\\]", type_link(titles, inner), len)
+            }
+            Typ::Set(inner) => format!("set of {}", type_link(titles, inner)),
+            Typ::Map(key, value) => format!(
+                "map of {} → {}",
+                type_link(titles, key),
+                type_link(titles, value)
+            ),
+            Typ::Tuple(fields) => {
+                let types: Vec<_> = fields.iter().map(|f| type_link(titles, f)).collect();
+                format!("({})", types.join(", "))
+            }
+            Typ::Ref { path, type_name } => link(title(titles, path, type_name)),
+            typ => format!("`{}`", typ),
+        },
+        Field::Container(container) => link(title(titles, &container.path, &container.type_name)),
+        Field::Enum(enum_type) => link(title(titles, &enum_type.path, &enum_type.type_name)),
+    }
+}
+
+/// Makes Markdown fit in a table cell, keeping line breaks.
+fn table_cell(text: &str) -> String {
+    text.trim().replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn render_heading(result: &mut String, title: &str, doc: &str) {
+    result.push_str(&format!("## {}\n\n", escape(title)));
+    if !doc.is_empty() {
+        result.push_str(&format!("{}\n\n", doc));
+    }
+}
+
+fn render_table(result: &mut String, titles: &Titles, fields: &[Field]) {
+    result.push_str("| Field | Type | Required | Description |\n");
+    result.push_str("| --- | --- | --- | --- |\n");
+    for field in visible(fields) {
+        let doc = field.doc();
+        let flattened = flattened(field);
+        let output = field.direction() == Direction::Output;
+        let map = matches!(flattened, Some(Flattened::Map(_)));
+        let required = field.default().is_none() && !field.is_optional() && !output && !map;
+        let mut description = table_cell(doc);
+        if output {
+            description.push_str("<br>Output only: written, but never read");
+        }
+        if let Some(aliases) = also_accepted(field.aliases()) {
+            description.push_str(&format!("<br>Also accepted: {}", aliases));
+        }
+        if let Some(written) = written_name(field) {
+            description.push_str(&format!("<br>Serialized as `{}`", written));
+        }
+        if let Some(format) = format(field) {
+            description.push_str(&format!("<br>Written as {}", format.description()));
+        }
+        if let Some(default) = field.default() {
+            description.push_str(&format!("<br>Default: `{}`", default));
+        }
+        if let Some(example) = field.example() {
+            description.push_str(&format!("<br>Example: `{}`", example));
+        }
+        // A flattened struct has no section of its own to link to.
+        if let Some(source) = field.flattened_from() {
+            description.push_str(&format!("<br>From `{}`", source));
+        }
+        // A flattened map or enum has no key of its own.
+        let (name, type_link) = match &flattened {
+            Some(flattened @ Flattened::Map(value)) => {
+                (format!("*{}*", flattened.label()), type_link(titles, value))
+            }
+            Some(flattened) => (format!("*{}*", flattened.label()), type_link(titles, field)),
+            None => (format!("`{}`", shown_name(field)), type_link(titles, field)),
+        };
+        result.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            name,
+            type_link,
+            if required { "yes" } else { "no" },
+            description.trim_start_matches("<br>")
+        ));
+    }
+}
+
+fn render_enum(titles: &Titles, enum_type: &Enum) -> String {
+    let mut result = String::new();
+    let heading = title(titles, &enum_type.path, &enum_type.type_name);
+    render_heading(&mut result, heading, &enum_type.type_doc);
+    if let Some(description) = describe_representation(enum_type) {
+        result.push_str(&format!("{}\n\n", description));
+    }
+    result.push_str("Variants:\n\n");
+    for variant in visible_variants(enum_type) {
+        let doc = variant.doc.replace('\n', "\n  ");
+        let mut annotations = match &variant.fields {
+            VariantFields::Unnamed(fields) => {
+                visible(fields).map(|f| type_link(titles, f)).collect()
+            }
+            VariantFields::Unit | VariantFields::Named(_) => vec![],
+        };
+        if let Some(aliases) = also_accepted(&variant.aliases) {
+            annotations.push(format!("also accepted: {}", aliases));
+        }
+        let label = if annotations.is_empty() {
+            format!("`{}`", variant.name)
+        } else {
+            format!("`{}` ({})", variant.name, annotations.join(", "))
+        };
+        match &variant.fields {
+            VariantFields::Named(fields) => {
+                result.push_str(&format!("- {}: {}\n\n", label, doc));
+                let mut table = String::new();
+                render_table(&mut table, titles, fields);
+                for line in table.lines() {
+                    result.push_str(&format!("  {}\n", line));
+                }
+                result.push('\n');
+            }
+            VariantFields::Unit | VariantFields::Unnamed(_) => {
+                result.push_str(&format!("- {}: {}\n", label, doc));
+            }
+        }
+    }
+    if let Some(catch_all) = describe_catch_all(enum_type) {
+        result.push_str(&format!("\n{}\n", catch_all));
+    }
+    result
+}
+
+fn render_container(titles: &Titles, container: &Container) -> String {
+    let mut result = String::new();
+    let heading = title(titles, &container.path, &container.type_name);
+    render_heading(&mut result, heading, &container.type_doc);
+    if container.deny_unknown_fields {
+        result.push_str("Unknown keys are rejected.\n\n");
+    }
+    render_table(&mut result, titles, &container.fields);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::fixtures::{config, container, primitive};
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_generic_heading() {
+        let doc = container(
+            "Page<u32>",
+            "Page<u32>",
+            "One page",
+            vec![primitive("total", Typ::U32, "The number of results")],
+        );
+
+        let result = Markdown.render(doc.clone());
+
+        assert!(result.starts_with("## Page\\<u32\\>\n\nOne page\n"));
+        assert_eq!(anchor("Page<u32>"), "pageu32");
+        let outer = container("Outer", "Outer", "", vec![doc]);
+        assert!(Markdown
+            .render(outer)
+            .contains("| `Page<u32>` | [`Page<u32>`](#pageu32) | yes | One page |"));
+    }
+
+    #[test]
+    fn test_markdown_renderer() {
+        let mut doc = config();
+        let Field::Container(config) = &mut doc else {
+            unreachable!()
+        };
+        config.type_doc = "The service config.\n\nSee the *manual* for details.".to_string();
+        config.fields[1].set_doc("Sockets to listen on.\nAt least one is needed.");
+
+        let result = Markdown.render(doc);
+
+        let expected = indoc! {"
+            ## Config
+
+            The service config.
+
+            See the *manual* for details.
+
+            | Field | Type | Required | Description |
+            | --- | --- | --- | --- |
+            | `name` | `String` | yes | The service name |
+            | `listeners` | Vec\\<[`Listener`](#listener)\\> | yes | Sockets to listen on.<br>At least one is needed. |
+            | `level` | [`Level`](#level) | yes | Log level |
+            | `tags` | Vec\\<`String`\\> | yes | Tags to attach |
+
+            ## Listener
+
+            A listening socket
+
+            | Field | Type | Required | Description |
+            | --- | --- | --- | --- |
+            | `host` | `String` | no | The host to bind |
+            | `port` | `u16` | no | The port to bind<br>Default: `8080` |
+
+            ## Level
+
+            Log level
+
+            Variants:
+
+            - `error`: Only errors
+            - `debug`: Everything
+        "};
+
+        assert_eq!(result, expected);
+    }
+}
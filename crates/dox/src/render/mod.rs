@@ -1,3 +1,518 @@
+mod json_schema;
+mod markdown;
 mod text;
+mod toml_example;
+mod yaml_example;
 
+pub use json_schema::*;
+pub use markdown::*;
 pub use text::*;
+pub use toml_example::*;
+pub use yaml_example::*;
+
+use std::collections::HashSet;
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    Direction, Enum, Field, Format, Primitive, Representation, Schema, Typ, Variant, VariantFields,
+};
+
+/// Describes the serialized shape of an enum, if it is more than a plain name.
+pub(crate) fn describe_representation(enum_type: &Enum) -> Option<String> {
+    match &enum_type.representation {
+        Representation::External => {
+            let has_data = enum_type
+                .variants
+                .iter()
+                .any(|v| v.fields != VariantFields::Unit);
+            has_data.then(|| {
+                "Written as the variant name, or for variants with data, an object \
+                 with the variant name as its only key."
+                    .to_string()
+            })
+        }
+        Representation::Internal { tag } => Some(format!(
+            "Written as an object with `{}` field selecting one of the variants below, \
+             alongside the variant's fields.",
+            tag
+        )),
+        Representation::Adjacent { tag, content } => Some(format!(
+            "Written as an object with `{}` field selecting one of the variants below, \
+             and `{}` field holding the variant's data.",
+            tag, content
+        )),
+        Representation::Untagged => Some(
+            "Written as the data of one of the variants below, with no tag; \
+             the first variant that matches is used."
+                .to_string(),
+        ),
+    }
+}
+
+/// Renders a section for each named type a schema documents, from its root,
+/// each followed by the sections for the types its visible fields use. Each
+/// type is rendered once. `section` renders a container or enum, given the
+/// paths of the types whose sections led to it, ending with its own, which
+/// fields of recursive types refer back to.
+pub(crate) fn render_sections(
+    schema: &Schema,
+    mut section: impl FnMut(&Field, &[String]) -> String,
+) -> String {
+    let mut sections = Vec::new();
+    render_type(
+        schema,
+        &schema.root,
+        &mut HashSet::new(),
+        &mut Vec::new(),
+        &mut sections,
+        &mut section,
+    );
+    sections.join("\n")
+}
+
+fn render_type(
+    schema: &Schema,
+    field: &Field,
+    seen: &mut HashSet<String>,
+    enclosing: &mut Vec<String>,
+    sections: &mut Vec<String>,
+    section: &mut impl FnMut(&Field, &[String]) -> String,
+) {
+    let (path, nested): (_, Vec<_>) = match field {
+        Field::Container(container) => (&container.path, visible(&container.fields).collect()),
+        Field::Enum(enum_type) => (
+            &enum_type.path,
+            visible_variants(enum_type)
+                .flat_map(|v| visible(v.fields.fields()))
+                .collect(),
+        ),
+        Field::Primitive(_) => return,
+    };
+    if !seen.insert(path.clone()) {
+        return;
+    }
+    enclosing.push(path.clone());
+    sections.push(section(field, enclosing));
+    for field in nested {
+        for definition in schema.nested(field) {
+            render_type(schema, definition, seen, enclosing, sections, section);
+        }
+    }
+    enclosing.pop();
+}
+
+/// The fields shown in human-readable documentation.
+pub(crate) fn visible(fields: &[Field]) -> impl Iterator<Item = &Field> {
+    fields.iter().filter(|f| !f.is_hidden())
+}
+
+/// Whether a field is read from a config. Fields that serde only writes are
+/// documented, but left out of examples.
+pub(crate) fn is_input(field: &Field) -> bool {
+    field.direction() != Direction::Output
+}
+
+/// The name a field is documented under: the name serde writes for a field
+/// that is only written, and otherwise the name it reads.
+pub(crate) fn shown_name(field: &Field) -> &str {
+    match field.serialized_name() {
+        Some(name) if !is_input(field) => name,
+        _ => field.name(),
+    }
+}
+
+/// The name serde writes a field under, where it differs from the name shown.
+pub(crate) fn written_name(field: &Field) -> Option<&str> {
+    field
+        .serialized_name()
+        .filter(|name| *name != shown_name(field))
+}
+
+/// The variants shown in human-readable documentation.
+pub(crate) fn visible_variants(enum_type: &Enum) -> impl Iterator<Item = &Variant> {
+    enum_type.variants.iter().filter(|v| !v.hidden)
+}
+
+/// What a flattened map or enum adds to its parent, in place of a key of its own.
+pub(crate) enum Flattened<'a> {
+    /// Any other keys, each holding a value like this one.
+    Map(&'a Field),
+    /// The keys of one of the variants of the enum of this name.
+    Enum(&'a str),
+}
+
+impl Flattened<'_> {
+    /// Stands in for the field's name in human-readable documentation.
+    pub(crate) fn label(&self) -> String {
+        match self {
+            Flattened::Map(_) => "any other key".to_string(),
+            Flattened::Enum(type_name) => format!("the keys of a {} variant", type_name),
+        }
+    }
+}
+
+/// How a field's keys sit in its parent, if it is a flattened map or enum.
+pub(crate) fn flattened(field: &Field) -> Option<Flattened<'_>> {
+    if !field.is_flattened() {
+        return None;
+    }
+    match field {
+        Field::Primitive(Primitive {
+            typ: Typ::Map(_, value),
+            ..
+        }) => Some(Flattened::Map(value)),
+        // A schema refers to the enum rather than including it.
+        Field::Primitive(Primitive {
+            typ: Typ::Ref { type_name, .. },
+            ..
+        }) => Some(Flattened::Enum(type_name)),
+        Field::Enum(enum_type) => Some(Flattened::Enum(&enum_type.type_name)),
+        _ => None,
+    }
+}
+
+/// The names of an enum's variants, including their aliases, quoted as they are
+/// written in a config.
+pub(crate) fn variant_names(enum_type: &Enum) -> String {
+    let names: Vec<_> = visible_variants(enum_type)
+        .flat_map(|v| std::iter::once(&v.name).chain(&v.aliases))
+        .map(|name| format!("\"{}\"", name))
+        .collect();
+    let mut names = names.join(", ");
+    if enum_type.variants.iter().any(|v| v.other) {
+        names.push_str(", or any other value");
+    }
+    names
+}
+
+/// The paths and names of the types a field refers to rather than includes,
+/// looking through collection types to their elements.
+pub(crate) fn references(field: &Field) -> Vec<(&str, &str)> {
+    match field {
+        Field::Primitive(prim) => match &prim.typ {
+            Typ::Ref { path, type_name } => vec![(path, type_name)],
+            Typ::Vec(inner) | Typ::Array(inner, _) | Typ::Set(inner) => references(inner),
+            Typ::Map(key, value) => {
+                let mut found = references(key);
+                found.extend(references(value));
+                found
+            }
+            Typ::Tuple(fields) => fields.iter().flat_map(references).collect(),
+            _ => vec![],
+        },
+        Field::Container(_) | Field::Enum(_) => vec![],
+    }
+}
+
+/// The other names accepted for a field or variant, quoted, if it has any.
+pub(crate) fn also_accepted(aliases: &[String]) -> Option<String> {
+    let aliases: Vec<_> = aliases.iter().map(|a| format!("`{}`", a)).collect();
+    (!aliases.is_empty()).then(|| aliases.join(", "))
+}
+
+/// Describes the variant that unknown values map to, if an enum has one.
+pub(crate) fn describe_catch_all(enum_type: &Enum) -> Option<String> {
+    enum_type
+        .variants
+        .iter()
+        .find(|v| v.other)
+        .map(|v| format!("Any other value maps to {}.", v.name))
+}
+
+/// The format of a field's value, if it has a conventional one.
+pub(crate) fn format(field: &Field) -> Option<&Format> {
+    match field {
+        Field::Primitive(prim) => match &prim.typ {
+            Typ::Formatted(format) => Some(format),
+            _ => None,
+        },
+        Field::Container(_) | Field::Enum(_) => None,
+    }
+}
+
+/// An example value for a field, as it would be serialized: its example or
+/// default where one is known, and otherwise a placeholder appropriate to its type.
+pub(crate) fn example_value(field: &Field) -> Value {
+    if let Some(value) = field.example() {
+        return value.clone();
+    }
+    if let Some(value) = field.default().and_then(|d| d.value.clone()) {
+        return value;
+    }
+    match field {
+        Field::Primitive(prim) => match &prim.typ {
+            Typ::F32 | Typ::F64 => json!(0.0),
+            Typ::Bool => json!(false),
+            Typ::Char => json!("a"),
+            Typ::String | Typ::PathBuf => json!(""),
+            // A recursive type refers back to itself, so its example stops here.
+            Typ::Vec(inner) | Typ::Set(inner) if !references(inner).is_empty() => json!([]),
+            Typ::Vec(inner) | Typ::Set(inner) => json!([example_value(inner)]),
+            Typ::Array(inner, len) => Value::Array(vec![example_value(inner); *len]),
+            Typ::Map(_, value) if !references(value).is_empty() => json!({}),
+            Typ::Map(_, value) => json!({ "name": example_value(value) }),
+            Typ::Tuple(fields) => Value::Array(fields.iter().map(example_value).collect()),
+            Typ::Unit => Value::Null,
+            Typ::Formatted(format) => match format {
+                Format::Duration => json!({ "secs": 0, "nanos": 0 }),
+                Format::SystemTime => json!({ "secs_since_epoch": 0, "nanos_since_epoch": 0 }),
+                Format::IpAddr | Format::Ipv4Addr => json!("127.0.0.1"),
+                Format::Ipv6Addr => json!("::1"),
+                Format::SocketAddr | Format::SocketAddrV4 => json!("127.0.0.1:8080"),
+                Format::SocketAddrV6 => json!("[::1]:8080"),
+                Format::NonZero(_) => json!(1),
+            },
+            Typ::Ref { .. } => json!({}),
+            _ => json!(0),
+        },
+        Field::Container(container) => Value::Object(example_fields(&container.fields)),
+        Field::Enum(enum_type) => {
+            let Some(variant) = visible_variants(enum_type).next() else {
+                return Value::Null;
+            };
+            let payload = match &variant.fields {
+                VariantFields::Unit => None,
+                VariantFields::Unnamed(fields) if fields.len() == 1 => {
+                    Some(example_value(&fields[0]))
+                }
+                VariantFields::Unnamed(fields) => {
+                    Some(Value::Array(fields.iter().map(example_value).collect()))
+                }
+                VariantFields::Named(fields) => Some(Value::Object(example_fields(fields))),
+            };
+            let name = json!(variant.name);
+            match (&enum_type.representation, payload) {
+                (Representation::External, None) => name,
+                (Representation::External, Some(payload)) => {
+                    json!({ variant.name.clone(): payload })
+                }
+                (Representation::Internal { tag }, payload) => {
+                    let mut object = Map::new();
+                    object.insert(tag.clone(), name);
+                    if let Some(Value::Object(fields)) = payload {
+                        object.extend(fields);
+                    }
+                    Value::Object(object)
+                }
+                (Representation::Adjacent { tag, .. }, None) => json!({ tag.clone(): name }),
+                (Representation::Adjacent { tag, content }, Some(payload)) => {
+                    json!({ tag.clone(): name, content.clone(): payload })
+                }
+                (Representation::Untagged, payload) => payload.unwrap_or(Value::Null),
+            }
+        }
+    }
+}
+
+/// Whether an example leaves a field out, or shows it commented out: it may
+/// be omitted and has no example or default to show, or its default is not
+/// known, so a placeholder would be taken for it.
+pub(crate) fn left_out(field: &Field) -> bool {
+    if field.example().is_some() {
+        return false;
+    }
+    match field.default() {
+        Some(default) => default.value.is_none(),
+        None => field.is_optional(),
+    }
+}
+
+/// Example values for the fields of an object. Hidden and output-only fields,
+/// and fields an example leaves out, are left out.
+fn example_fields(fields: &[Field]) -> Map<String, Value> {
+    visible(fields)
+        .filter(|f| is_input(f))
+        .filter(|f| !left_out(f))
+        .flat_map(|f| match flattened(f) {
+            Some(_) => flattened_keys(example_value(f)),
+            None => Map::from_iter([(f.name().to_string(), example_value(f))]),
+        })
+        .collect()
+}
+
+/// The value an example shows for a field, and whether it shows it commented
+/// out. Fields that may be left out, or whose default is nothing or unknown,
+/// are commented out, as are the example keys of a flattened map; a field
+/// with no default to show takes a placeholder.
+pub(crate) fn example_entry(field: &Field) -> (Value, bool) {
+    let mut commented = left_out(field) || matches!(flattened(field), Some(Flattened::Map(_)));
+    let mut value = example_value(field);
+    if value.is_null() {
+        let mut placeholder = field.clone();
+        placeholder.set_default(None);
+        value = example_value(&placeholder);
+        commented = true;
+    }
+    (value, commented)
+}
+
+/// The keys a flattened field's example adds to its parent, alongside the
+/// parent's own. A value that is not an object has no keys to add.
+pub(crate) fn flattened_keys(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(entries) => entries,
+        _ => Map::new(),
+    }
+}
+
+/// A key as written in a TOML or YAML example: bare if it is made of letters,
+/// digits, `_` and `-`, and quoted otherwise.
+pub(crate) fn key(name: &str) -> String {
+    let bare = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        name.to_string()
+    } else {
+        Value::from(name).to_string()
+    }
+}
+
+/// Writes text as `#` comments at the given indent.
+pub(crate) fn comment(result: &mut String, indent: usize, text: &str) {
+    let pad = " ".repeat(indent);
+    for line in text.lines() {
+        if line.is_empty() {
+            result.push_str(&format!("{}#\n", pad));
+        } else {
+            result.push_str(&format!("{}# {}\n", pad, line));
+        }
+    }
+}
+
+/// Writes a field's documentation as comments above its example entry,
+/// followed by notes on the values it takes.
+pub(crate) fn example_comments(result: &mut String, indent: usize, field: &Field) {
+    comment(result, indent, field.doc());
+    if let Some(aliases) = also_accepted(field.aliases()) {
+        comment(result, indent, &format!("Also accepted: {}", aliases));
+    }
+    for nested in field.nested() {
+        if let Field::Enum(enum_type) = nested {
+            let values = variant_names(enum_type);
+            comment(result, indent, &format!("Allowed values: {}", values));
+        }
+    }
+    for (_, type_name) in references(field) {
+        comment(result, indent, &format!("See {}", type_name));
+    }
+    if let Some(flattened) = flattened(field) {
+        comment(result, indent, &format!("Flattened: {}", flattened.label()));
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod fixtures {
+    //! Field trees shared by the renderers' tests.
+
+    use crate::{
+        Container, DefaultKind, DefaultValue, Enum, Field, FieldMeta, Primitive, Representation,
+        Typ, Variant, VariantFields,
+    };
+
+    pub fn primitive(name: &str, typ: Typ, doc: &str) -> Field {
+        Field::Primitive(Primitive {
+            meta: FieldMeta {
+                name: name.to_string(),
+                doc: doc.to_string(),
+                ..FieldMeta::default()
+            },
+            typ,
+            type_name: None,
+        })
+    }
+
+    /// A struct, whose path is its type name.
+    pub fn container(name: &str, type_name: &str, doc: &str, fields: Vec<Field>) -> Field {
+        Field::Container(Container {
+            meta: FieldMeta {
+                name: name.to_string(),
+                doc: doc.to_string(),
+                ..FieldMeta::default()
+            },
+            type_name: type_name.to_string(),
+            path: type_name.to_string(),
+            type_doc: doc.to_string(),
+            fields,
+            deny_unknown_fields: false,
+        })
+    }
+
+    /// An enum, whose path is its type name.
+    pub fn enumeration(
+        name: &str,
+        type_name: &str,
+        doc: &str,
+        variants: Vec<Variant>,
+        representation: Representation,
+    ) -> Field {
+        Field::Enum(Enum {
+            meta: FieldMeta {
+                name: name.to_string(),
+                doc: doc.to_string(),
+                ..FieldMeta::default()
+            },
+            type_name: type_name.to_string(),
+            path: type_name.to_string(),
+            type_doc: doc.to_string(),
+            variants,
+            representation,
+        })
+    }
+
+    pub fn variant(name: &str, doc: &str, fields: VariantFields) -> Variant {
+        Variant {
+            name: name.to_string(),
+            doc: doc.to_string(),
+            fields,
+            hidden: false,
+            aliases: Vec::new(),
+            serialized_name: None,
+            other: false,
+        }
+    }
+
+    /// A service config with a list of listener structs, a log level enum and
+    /// a list of tags.
+    pub fn config() -> Field {
+        let mut port = primitive("port", Typ::U16, "The port to bind");
+        port.set_default(Some(DefaultValue {
+            kind: DefaultKind::Trait,
+            value: Some(8080.into()),
+        }));
+        let mut host = primitive("host", Typ::String, "The host to bind");
+        host.set_optional(true);
+        let listener = container("", "Listener", "A listening socket", vec![host, port]);
+        let level = enumeration(
+            "level",
+            "Level",
+            "Log level",
+            vec![
+                variant("error", "Only errors", VariantFields::Unit),
+                variant("debug", "Everything", VariantFields::Unit),
+            ],
+            Representation::External,
+        );
+        container(
+            "Config",
+            "Config",
+            "The service config",
+            vec![
+                primitive("name", Typ::String, "The service name"),
+                primitive(
+                    "listeners",
+                    Typ::Vec(Box::new(listener)),
+                    "Sockets to listen on",
+                ),
+                level,
+                primitive(
+                    "tags",
+                    Typ::Vec(Box::new(primitive("", Typ::String, ""))),
+                    "Tags to attach",
+                ),
+            ],
+        )
+    }
+}
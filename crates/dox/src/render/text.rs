@@ -1,79 +1,134 @@
-use crate::{Container, Enum, Field, Renderer};
+use super::{
+    also_accepted, describe_catch_all, describe_representation, flattened, format, references,
+    render_sections, shown_name, visible, visible_variants, written_name, Flattened,
+};
+use crate::{Container, Direction, Enum, Field, Primitive, Renderer, Schema, Typ, VariantFields};
 
 pub struct Text;
 
 impl Renderer for Text {
     fn render(&self, doc_type: Field) -> String {
-        match doc_type {
-            Field::Container(container) => render_container(&container),
-            Field::Primitive(_) => String::new(),
-            Field::Enum(enum_type) => render_enum(&enum_type),
-        }
+        self.render_schema(&Schema::new(doc_type))
+    }
+
+    fn render_schema(&self, schema: &Schema) -> String {
+        render_sections(schema, |field, enclosing| match field {
+            Field::Container(container) => render_container(container, enclosing),
+            Field::Enum(enum_type) => render_enum(enum_type, enclosing),
+            Field::Primitive(_) => unreachable!(),
+        })
     }
 }
 
-fn render_enum(enum_type: &Enum) -> String {
+fn render_enum(enum_type: &Enum, enclosing: &[String]) -> String {
     let mut result = String::new();
-    result.push_str(&format!("{} (enum)\n", enum_type.name));
-    result.push_str(&format!("{}\n", "=".repeat(enum_type.name.len() + 7)));
-    result.push_str(&format!("{}\n\n", enum_type.doc));
+    let title = format!("{} (enum)", enum_type.type_name);
+    result.push_str(&format!("{}\n", title));
+    result.push_str(&format!("{}\n", "=".repeat(title.chars().count())));
+    result.push_str(&format!("{}\n\n", enum_type.type_doc));
+    if let Some(description) = describe_representation(enum_type) {
+        result.push_str(&format!("{}\n\n", description));
+    }
     result.push_str("Variants:\n");
-    for variant in &enum_type.variants {
-        result.push_str(&format!("- {}: {}\n", variant.name, variant.doc));
+    for variant in visible_variants(enum_type) {
+        let mut annotations = match &variant.fields {
+            VariantFields::Unnamed(fields) => visible(fields).map(Field::type_name).collect(),
+            VariantFields::Unit | VariantFields::Named(_) => vec![],
+        };
+        if let Some(aliases) = also_accepted(&variant.aliases) {
+            annotations.push(format!("also accepted: {}", aliases));
+        }
+        if annotations.is_empty() {
+            result.push_str(&format!("- {}: {}\n", variant.name, variant.doc));
+        } else {
+            result.push_str(&format!(
+                "- {} ({}): {}\n",
+                variant.name,
+                annotations.join(", "),
+                variant.doc
+            ));
+        }
+        if let VariantFields::Named(fields) = &variant.fields {
+            for field in visible(fields) {
+                result.push_str(&format!("  {}", render_field(field, enclosing)));
+            }
+        }
+    }
+    if let Some(catch_all) = describe_catch_all(enum_type) {
+        result.push_str(&format!("\n{}\n", catch_all));
     }
     result
 }
 
-fn render_container(container: &Container) -> String {
+fn render_field(field: &Field, enclosing: &[String]) -> String {
+    let flattened = flattened(field);
+    let mut annotations = match &flattened {
+        Some(Flattened::Map(value)) => vec![value.type_name()],
+        _ => vec![field.type_name()],
+    };
+    if let Field::Primitive(Primitive {
+        typ: Typ::Set(_), ..
+    }) = field
+    {
+        annotations.push("unique items".to_string());
+    }
+    if field.is_optional() {
+        annotations.push("optional".to_string());
+    }
+    if field.direction() == Direction::Output {
+        annotations.push("output only".to_string());
+    }
+    if let Some(aliases) = also_accepted(field.aliases()) {
+        annotations.push(format!("also accepted: {}", aliases));
+    }
+    if let Some(written) = written_name(field) {
+        annotations.push(format!("serialized as `{}`", written));
+    }
+    if let Some(format) = format(field) {
+        annotations.push(format!("written as {}", format.description()));
+    }
+    if let Some(default) = field.default() {
+        annotations.push(format!("default: {}", default));
+    }
+    if let Some(example) = field.example() {
+        annotations.push(format!("example: {}", example));
+    }
+    if let Some(source) = field.flattened_from() {
+        annotations.push(format!("from {}", source));
+    }
+    for (path, type_name) in references(field) {
+        if enclosing.iter().any(|p| p == path) {
+            annotations.push(format!("see {}", type_name));
+        }
+    }
+    // A flattened map or enum has no key of its own.
+    let name = match &flattened {
+        Some(flattened) => flattened.label(),
+        None => shown_name(field).to_string(),
+    };
+    format!("- {} ({}): {}\n", name, annotations.join(", "), field.doc())
+}
+
+fn render_container(container: &Container, enclosing: &[String]) -> String {
     let mut result = String::new();
     result.push_str(&format!("{}\n", container.type_name));
     result.push_str(&format!("{}\n", "=".repeat(container.type_name.len())));
-    result.push_str(&format!("{}\n\n", container.doc));
-
-    for field in &container.fields {
-        match field {
-            Field::Primitive(prim) => {
-                result.push_str(&format!("- {} ({}): {}\n", prim.name, prim.typ, prim.doc));
-            }
-            Field::Container(nested) => {
-                result.push_str(&format!(
-                    "- {} ({}): {}\n",
-                    nested.name, nested.type_name, nested.doc
-                ));
-            }
-            Field::Enum(enum_type) => {
-                result.push_str(&format!("- {} (enum): {}\n", enum_type.name, enum_type.doc));
-            }
-        }
+    result.push_str(&format!("{}\n\n", container.type_doc));
+    if container.deny_unknown_fields {
+        result.push_str("Unknown keys are rejected.\n\n");
     }
 
-    let nested_fields: Vec<_> = container
-        .fields
-        .iter()
-        .filter(|f| matches!(f, Field::Container(_) | Field::Enum(_)))
-        .collect();
-
-    if !nested_fields.is_empty() {
-        result.push('\n');
-        for (i, field) in nested_fields.iter().enumerate() {
-            result.push_str(&match field {
-                Field::Container(nested) => render_container(nested),
-                Field::Enum(enum_type) => render_enum(enum_type),
-                _ => unreachable!(),
-            });
-            if i < nested_fields.len() - 1 {
-                result.push('\n');
-            }
-        }
+    for field in visible(&container.fields) {
+        result.push_str(&render_field(field, enclosing));
     }
-
     result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Container, Field, Primitive, Renderer, Typ, Variant};
+    use crate::render::fixtures::*;
+    use crate::{DefaultKind, DefaultValue, Field, Primitive, Renderer, Representation, Typ};
     use indoc::indoc;
     use pretty_assertions::assert_eq;
 
@@ -93,47 +148,35 @@ mod tests {
 
     #[test]
     fn test_text_renderer() {
-        let doc = Field::Container(Container {
-            name: "TestStruct".to_string(),
-            type_name: "TestStruct".to_string(),
-            doc: "This is a test struct".to_string(),
-            fields: vec![
-                Field::Primitive(Primitive {
-                    name: "field1".to_string(),
-                    typ: Typ::String,
-                    doc: "A string field".to_string(),
-                }),
-                Field::Primitive(Primitive {
-                    name: "field2".to_string(),
-                    typ: Typ::I32,
-                    doc: "An integer field".to_string(),
-                }),
-                Field::Container(Container {
-                    name: "nested".to_string(),
-                    type_name: "NestedStruct".to_string(),
-                    doc: "A nested struct".to_string(),
-                    fields: vec![Field::Primitive(Primitive {
-                        name: "nested_field".to_string(),
-                        typ: Typ::String,
-                        doc: "A field in the nested struct".to_string(),
-                    })],
-                }),
-                Field::Enum(Enum {
-                    name: "enum_field".to_string(),
-                    doc: "An enum field".to_string(),
-                    variants: vec![
-                        Variant {
-                            name: "Variant1".to_string(),
-                            doc: "First variant".to_string(),
-                        },
-                        Variant {
-                            name: "Variant2".to_string(),
-                            doc: "Second variant".to_string(),
-                        },
+        let doc = container(
+            "TestStruct",
+            "TestStruct",
+            "This is a test struct",
+            vec![
+                primitive("field1", Typ::String, "A string field"),
+                primitive("field2", Typ::I32, "An integer field"),
+                container(
+                    "nested",
+                    "NestedStruct",
+                    "A nested struct",
+                    vec![primitive(
+                        "nested_field",
+                        Typ::String,
+                        "A field in the nested struct",
+                    )],
+                ),
+                enumeration(
+                    "enum_field",
+                    "EnumField",
+                    "An enum field",
+                    vec![
+                        variant("Variant1", "First variant", VariantFields::Unit),
+                        variant("Variant2", "Second variant", VariantFields::Unit),
                     ],
-                }),
+                    Representation::External,
+                ),
             ],
-        });
+        );
 
         let renderer = Text;
         let result = renderer.render(doc);
@@ -146,7 +189,7 @@ mod tests {
             - field1 (String): A string field
             - field2 (i32): An integer field
             - nested (NestedStruct): A nested struct
-            - enum_field (enum): An enum field
+            - enum_field (EnumField): An enum field
 
             NestedStruct
             ============
@@ -154,8 +197,8 @@ mod tests {
 
             - nested_field (String): A field in the nested struct
 
-            enum_field (enum)
-            =================
+            EnumField (enum)
+            ================
             An enum field
 
             Variants:
@@ -165,4 +208,238 @@ mod tests {
 
         strip_and_compare(&result, expected);
     }
+
+    #[test]
+    fn test_text_renderer_vec_of_containers() {
+        let doc = container(
+            "Server",
+            "Server",
+            "A server",
+            vec![primitive(
+                "listeners",
+                Typ::Vec(Box::new(container(
+                    "",
+                    "Listener",
+                    "A listening socket",
+                    vec![primitive("port", Typ::U16, "The port to bind")],
+                ))),
+                "Sockets to listen on",
+            )],
+        );
+
+        let result = Text.render(doc);
+
+        let expected = indoc! {"
+            Server
+            ======
+            A server
+
+            - listeners (Vec<Listener>): Sockets to listen on
+
+            Listener
+            ========
+            A listening socket
+
+            - port (u16): The port to bind
+        "};
+
+        strip_and_compare(&result, expected);
+    }
+
+    #[test]
+    fn test_text_renderer_annotations() {
+        let mut host = primitive("host", Typ::String, "The host name");
+        host.set_example(Some("localhost".into()));
+        let mut port = primitive("port", Typ::U16, "The port");
+        port.set_optional(true);
+        port.set_default(Some(DefaultValue {
+            kind: DefaultKind::Trait,
+            value: Some(8080.into()),
+        }));
+        let mut secret = primitive("secret", Typ::String, "Not shown");
+        secret.set_hidden(true);
+        let mut timeout = primitive("timeout", Typ::U32, "The timeout");
+        timeout.set_optional(true);
+        timeout.set_flattened_from(Some("Common".to_string()));
+        let doc = container(
+            "Config",
+            "Config",
+            "A config",
+            vec![host, port, secret, timeout],
+        );
+
+        let result = Text.render(doc);
+
+        let expected = indoc! {"
+            Config
+            ======
+            A config
+
+            - host (String, example: \"localhost\"): The host name
+            - port (u16, optional, default: 8080): The port
+            - timeout (u32, optional, from Common): The timeout
+        "};
+
+        strip_and_compare(&result, expected);
+    }
+
+    #[test]
+    fn test_text_renderer_map() {
+        let doc = container(
+            "Config",
+            "Config",
+            "A config",
+            vec![primitive(
+                "profiles",
+                Typ::Map(
+                    Box::new(Field::Primitive(Primitive::new(Typ::String))),
+                    Box::new(container(
+                        "",
+                        "Profile",
+                        "A named profile",
+                        vec![primitive("level", Typ::U8, "Optimisation level")],
+                    )),
+                ),
+                "Profiles by name",
+            )],
+        );
+
+        let result = Text.render(doc);
+
+        let expected = indoc! {"
+            Config
+            ======
+            A config
+
+            - profiles (map of String → Profile): Profiles by name
+
+            Profile
+            =======
+            A named profile
+
+            - level (u8): Optimisation level
+        "};
+
+        strip_and_compare(&result, expected);
+    }
+
+    #[test]
+    fn test_text_renderer_sequences() {
+        use crate::Dox;
+        use std::collections::BTreeSet;
+
+        let field = |mut field: Field, name: &str, doc: &str| {
+            field.set_name(name);
+            field.set_doc(doc);
+            field
+        };
+        let doc = container(
+            "Config",
+            "Config",
+            "A config",
+            vec![
+                field(<[u8; 3]>::dox(), "rgb", "The colour"),
+                field(<(u32, u32)>::dox(), "range", "The port range"),
+                field(BTreeSet::<String>::dox(), "tags", "Tags to attach"),
+            ],
+        );
+
+        let result = Text.render(doc);
+
+        let expected = indoc! {"
+            Config
+            ======
+            A config
+
+            - rgb ([u8; 3]): The colour
+            - range ((u32, u32)): The port range
+            - tags (set of String, unique items): Tags to attach
+        "};
+
+        strip_and_compare(&result, expected);
+    }
+
+    #[test]
+    fn test_text_renderer_variant_payloads() {
+        let port = primitive("port", Typ::U16, "The port to bind");
+        let doc = enumeration(
+            "Mode",
+            "Mode",
+            "How to run",
+            vec![
+                variant("Plain", "No payload", VariantFields::Unit),
+                variant(
+                    "Compound",
+                    "A tuple payload",
+                    VariantFields::Unnamed(vec![
+                        Field::Primitive(Primitive::new(Typ::String)),
+                        container("", "Listener", "A listening socket", vec![port.clone()]),
+                    ]),
+                ),
+                variant(
+                    "Listen",
+                    "A struct payload",
+                    VariantFields::Named(vec![port]),
+                ),
+            ],
+            Representation::External,
+        );
+
+        let result = Text.render(doc);
+
+        let expected = indoc! {"
+            Mode (enum)
+            ===========
+            How to run
+
+            Written as the variant name, or for variants with data, an object with the variant name as its only key.
+
+            Variants:
+            - Plain: No payload
+            - Compound (String, Listener): A tuple payload
+            - Listen: A struct payload
+              - port (u16): The port to bind
+
+            Listener
+            ========
+            A listening socket
+
+            - port (u16): The port to bind
+        "};
+
+        strip_and_compare(&result, expected);
+    }
+
+    #[test]
+    fn test_text_renderer_representation() {
+        let doc = enumeration(
+            "Action",
+            "Action",
+            "What to do",
+            vec![variant(
+                "Log",
+                "Write a message",
+                VariantFields::Named(vec![primitive("message", Typ::String, "The message")]),
+            )],
+            Representation::Internal {
+                tag: "type".to_string(),
+            },
+        );
+
+        let result = Text.render(doc);
+
+        let expected = indoc! {"
+            Action (enum)
+            =============
+            What to do
+
+            Written as an object with `type` field selecting one of the variants below, alongside the variant's fields.
+
+            Variants:
+            - Log: Write a message
+              - message (String): The message
+        "};
+
+        strip_and_compare(&result, expected);
+    }
 }
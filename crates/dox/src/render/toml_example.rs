@@ -0,0 +1,214 @@
+use serde_json::Value;
+
+use super::{
+    comment, example_comments, example_entry, flattened, flattened_keys, is_input, key, left_out,
+    visible,
+};
+use crate::{Container, Field, Renderer, Typ};
+
+/// Renders a complete, commented example TOML config. Each key carries its
+/// documentation as comments, and takes its default or a placeholder value.
+pub struct TomlExample;
+
+impl Renderer for TomlExample {
+    fn render(&self, doc_type: Field) -> String {
+        match doc_type {
+            Field::Container(container) => {
+                let mut result = String::new();
+                if !container.type_doc.is_empty() {
+                    comment(&mut result, 0, &container.type_doc);
+                    result.push('\n');
+                }
+                render_table(&mut result, &[], &container.fields);
+                result
+            }
+            Field::Primitive(_) | Field::Enum(_) => String::new(),
+        }
+    }
+}
+
+/// How a field that is written as a TOML table is headed.
+enum Section<'a> {
+    /// A nested struct, written as `[key]`.
+    Table(&'a Container),
+    /// A list of structs, written as `[[key]]`.
+    ArrayOfTables(&'a Container),
+    /// A map of structs, written as `[key.name]`.
+    MapOfTables(&'a Container),
+}
+
+fn section(field: &Field) -> Option<Section<'_>> {
+    // A flattened map's entries are keys of the table it is flattened into.
+    if field.is_flattened() {
+        return None;
+    }
+    match field {
+        Field::Container(container) => Some(Section::Table(container)),
+        Field::Primitive(prim) => match &prim.typ {
+            Typ::Vec(inner) => match inner.as_ref() {
+                Field::Container(container) => Some(Section::ArrayOfTables(container)),
+                _ => None,
+            },
+            Typ::Map(_, value) => match value.as_ref() {
+                Field::Container(container) => Some(Section::MapOfTables(container)),
+                _ => None,
+            },
+            _ => None,
+        },
+        Field::Enum(_) => None,
+    }
+}
+
+fn toml_value(value: &Value) -> String {
+    match value {
+        // TOML has no null, so an unset value is shown as an empty string.
+        Value::Null => "\"\"".to_string(),
+        Value::Bool(_) | Value::Number(_) | Value::String(_) => value.to_string(),
+        Value::Array(items) => {
+            let items: Vec<_> = items.iter().map(toml_value).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(entries) => {
+            let entries: Vec<_> = entries
+                .iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| format!("{} = {}", key(k), toml_value(v)))
+                .collect();
+            if entries.is_empty() {
+                "{}".to_string()
+            } else {
+                format!("{{ {} }}", entries.join(", "))
+            }
+        }
+    }
+}
+
+fn render_value(result: &mut String, field: &Field) {
+    example_comments(result, 0, field);
+    let (value, commented) = example_entry(field);
+    let prefix = if commented { "# " } else { "" };
+    if flattened(field).is_some() {
+        for (k, v) in flattened_keys(value) {
+            result.push_str(&format!("{}{} = {}\n", prefix, key(&k), toml_value(&v)));
+        }
+    } else {
+        result.push_str(&format!(
+            "{}{} = {}\n",
+            prefix,
+            key(field.name()),
+            toml_value(&value)
+        ));
+    }
+}
+
+fn render_table(result: &mut String, path: &[String], fields: &[Field]) {
+    // Plain keys must come before any table headers.
+    let (sections, values): (Vec<_>, Vec<_>) = visible(fields)
+        .filter(|f| is_input(f))
+        .partition(|f| section(f).is_some());
+    for (i, field) in values.iter().enumerate() {
+        if i > 0 {
+            result.push('\n');
+        }
+        render_value(result, field);
+    }
+
+    for field in sections {
+        if !result.is_empty() {
+            result.push('\n');
+        }
+        comment(result, 0, field.doc());
+        let mut path = path.to_vec();
+        path.push(key(field.name()));
+        let mut table = String::new();
+        let container = match section(field).unwrap() {
+            Section::Table(container) => {
+                table.push_str(&format!("[{}]\n", path.join(".")));
+                container
+            }
+            Section::ArrayOfTables(container) => {
+                table.push_str(&format!("[[{}]]\n", path.join(".")));
+                container
+            }
+            Section::MapOfTables(container) => {
+                path.push("name".to_string());
+                table.push_str(&format!("[{}]\n", path.join(".")));
+                container
+            }
+        };
+        render_table(&mut table, &path, &container.fields);
+        // Like a plain value, a section that may be left out is commented out.
+        if left_out(field) {
+            comment(result, 0, &table);
+        } else {
+            result.push_str(&table);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::fixtures::config;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_toml_example() {
+        let mut doc = config();
+        let Field::Container(config) = &mut doc else {
+            unreachable!()
+        };
+        config.fields[0].set_doc("The service name\n\nMust be unique.");
+
+        let result = TomlExample.render(doc);
+
+        let expected = indoc! {r#"
+            # The service config
+
+            # The service name
+            #
+            # Must be unique.
+            name = ""
+
+            # Log level
+            # Allowed values: "error", "debug"
+            level = "error"
+
+            # Tags to attach
+            tags = [""]
+
+            # Sockets to listen on
+            [[listeners]]
+            # The host to bind
+            # host = ""
+
+            # The port to bind
+            port = 8080
+        "#};
+
+        assert_eq!(result, expected);
+    }
+    #[test]
+    fn test_toml_example_optional_section() {
+        let mut doc = config();
+        let Field::Container(config) = &mut doc else {
+            unreachable!()
+        };
+        config.fields[1].set_optional(true);
+
+        let result = TomlExample.render(doc);
+
+        let expected = indoc! {r#"
+            # Sockets to listen on
+            # [[listeners]]
+            # # The host to bind
+            # # host = ""
+            #
+            # # The port to bind
+            # port = 8080
+        "#};
+
+        assert!(result.ends_with(expected), "{}", result);
+    }
+}
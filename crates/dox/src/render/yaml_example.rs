@@ -0,0 +1,198 @@
+use serde_json::Value;
+
+use super::{
+    comment, example_comments, example_entry, flattened, flattened_keys, is_input, key, visible,
+};
+use crate::{Field, Renderer, Typ};
+
+/// Renders a complete, commented example YAML config. Each key carries its
+/// documentation as comments, and takes its default or a placeholder value.
+pub struct YamlExample;
+
+impl Renderer for YamlExample {
+    fn render(&self, doc_type: Field) -> String {
+        match doc_type {
+            Field::Container(container) => {
+                let mut result = String::new();
+                if !container.type_doc.is_empty() {
+                    comment(&mut result, 0, &container.type_doc);
+                    result.push('\n');
+                }
+                render_fields(&mut result, 0, &container.fields);
+                result
+            }
+            Field::Primitive(_) | Field::Enum(_) => String::new(),
+        }
+    }
+}
+
+/// Writes a value in block style, following a `key:` or `-` that has already
+/// been written at the given indent.
+fn render_value(result: &mut String, indent: usize, value: &Value) {
+    let pad = " ".repeat(indent + 2);
+    match value {
+        Value::Array(items) if !items.is_empty() => {
+            result.push('\n');
+            for item in items {
+                result.push_str(&format!("{}-", pad));
+                render_value(result, indent + 2, item);
+            }
+        }
+        Value::Object(entries) if !entries.is_empty() => {
+            result.push('\n');
+            for (k, v) in entries {
+                result.push_str(&format!("{}{}:", pad, key(k)));
+                render_value(result, indent + 2, v);
+            }
+        }
+        Value::Array(_) => result.push_str(" []\n"),
+        Value::Object(_) => result.push_str(" {}\n"),
+        scalar => result.push_str(&format!(" {}\n", scalar)),
+    }
+}
+
+/// Writes a field's entry, expanding nested structs so that their fields keep
+/// their documentation.
+fn render_entry(result: &mut String, indent: usize, field: &Field, value: &Value) {
+    let pad = " ".repeat(indent);
+    result.push_str(&format!("{}{}:", pad, key(field.name())));
+    match field {
+        Field::Container(container) => render_block(result, indent + 2, &container.fields),
+        Field::Primitive(prim) => match &prim.typ {
+            Typ::Vec(inner) if matches!(inner.as_ref(), Field::Container(_)) => {
+                result.push_str(&format!("\n{}  -", pad));
+                render_block(result, indent + 4, container_fields(inner));
+            }
+            Typ::Map(_, value) if matches!(value.as_ref(), Field::Container(_)) => {
+                result.push_str(&format!("\n{}  name:", pad));
+                render_block(result, indent + 4, container_fields(value));
+            }
+            _ => render_value(result, indent, value),
+        },
+        Field::Enum(_) => render_value(result, indent, value),
+    }
+}
+
+/// Writes a struct's fields, following a `key:` or `-` that has already been
+/// written. A struct whose fields are all commented out is written as `{}`,
+/// since YAML reads a key or `-` with nothing after it as null.
+fn render_block(result: &mut String, indent: usize, fields: &[Field]) {
+    let mut block = String::new();
+    if render_fields(&mut block, indent, fields) {
+        result.push('\n');
+    } else {
+        result.push_str(" {}\n");
+    }
+    result.push_str(&block);
+}
+
+fn container_fields(field: &Field) -> &[Field] {
+    match field {
+        Field::Container(container) => &container.fields,
+        _ => &[],
+    }
+}
+
+/// Writes the entries of the given fields, returning whether any of them is
+/// left uncommented.
+fn render_fields(result: &mut String, indent: usize, fields: &[Field]) -> bool {
+    let mut active = false;
+    for field in visible(fields).filter(|f| is_input(f)) {
+        example_comments(result, indent, field);
+        let (value, commented) = example_entry(field);
+        let mut entry = String::new();
+        if flattened(field).is_some() {
+            for (k, v) in flattened_keys(value) {
+                entry.push_str(&format!("{}{}:", " ".repeat(indent), key(&k)));
+                render_value(&mut entry, indent, &v);
+            }
+        } else {
+            render_entry(&mut entry, indent, field, &value);
+        }
+        if commented {
+            for line in entry.lines() {
+                let (pad, rest) = line.split_at(indent);
+                result.push_str(&format!("{}# {}\n", pad, rest));
+            }
+        } else {
+            active |= !entry.is_empty();
+            result.push_str(&entry);
+        }
+    }
+    active
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::fixtures::{config, container, primitive};
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_yaml_example() {
+        let result = YamlExample.render(config());
+
+        let expected = indoc! {r#"
+            # The service config
+
+            # The service name
+            name: ""
+            # Sockets to listen on
+            listeners:
+              -
+                # The host to bind
+                # host: ""
+                # The port to bind
+                port: 8080
+            # Log level
+            # Allowed values: "error", "debug"
+            level: "error"
+            # Tags to attach
+            tags:
+              - ""
+        "#};
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_yaml_example_empty_blocks() {
+        let mut host = primitive("host", Typ::String, "The host to bind");
+        host.set_optional(true);
+        let listener = container("listener", "Listener", "A listening socket", vec![host]);
+        let listeners = primitive(
+            "listeners",
+            Typ::Vec(Box::new(listener.clone())),
+            "Sockets to listen on",
+        );
+        let by_name = primitive(
+            "by_name",
+            Typ::Map(
+                Box::new(primitive("", Typ::String, "")),
+                Box::new(listener.clone()),
+            ),
+            "Listeners by name",
+        );
+        let doc = container("Config", "Config", "", vec![listener, listeners, by_name]);
+
+        let expected = indoc! {r#"
+            # A listening socket
+            listener: {}
+              # The host to bind
+              # host: ""
+            # Sockets to listen on
+            listeners:
+              - {}
+                # The host to bind
+                # host: ""
+            # Listeners by name
+            by_name:
+              name: {}
+                # The host to bind
+                # host: ""
+        "#};
+
+        assert_eq!(YamlExample.render(doc), expected);
+    }
+}
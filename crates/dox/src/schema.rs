@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+
+use crate::{Dox, Field, Primitive, Typ, VariantFields};
+
+/// A registry of the named types reachable from a root field. Each container
+/// and enum is defined once, keyed by its type path, and the fields that use it
+/// refer to the definition with a [`Typ::Ref`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    /// The root field, with the named types it uses replaced by references.
+    /// A root container or enum is also the first definition.
+    pub root: Field,
+    /// The definition of each named type, in the order they are first used.
+    pub definitions: Vec<Field>,
+    index: HashMap<String, usize>,
+}
+
+impl Schema {
+    /// Collects the named types used by a field tree.
+    pub fn new(root: Field) -> Self {
+        let mut schema = Schema {
+            root: root.clone(),
+            definitions: Vec::new(),
+            index: HashMap::new(),
+        };
+        schema.root = match &root {
+            Field::Container(_) | Field::Enum(_) => {
+                let index = schema.define(&root);
+                schema.definitions[index].clone()
+            }
+            Field::Primitive(_) => schema.reference(&root),
+        };
+        schema
+    }
+
+    /// Collects the named types used by a type.
+    pub fn of<T: Dox>() -> Self {
+        Self::new(T::dox())
+    }
+
+    /// The definition of the named type with the given path.
+    pub fn definition(&self, path: &str) -> Option<&Field> {
+        self.index.get(path).map(|&index| &self.definitions[index])
+    }
+
+    /// The definitions a field refers to, looking through collection types to
+    /// their elements.
+    pub fn nested<'a>(&'a self, field: &'a Field) -> Vec<&'a Field> {
+        match field {
+            Field::Primitive(prim) => match &prim.typ {
+                Typ::Ref { path, .. } => self.definition(path).into_iter().collect(),
+                Typ::Vec(inner) | Typ::Array(inner, _) | Typ::Set(inner) => self.nested(inner),
+                Typ::Map(key, value) => {
+                    let mut nested = self.nested(key);
+                    nested.extend(self.nested(value));
+                    nested
+                }
+                Typ::Tuple(fields) => fields.iter().flat_map(|f| self.nested(f)).collect(),
+                _ => vec![],
+            },
+            Field::Container(_) | Field::Enum(_) => vec![field],
+        }
+    }
+
+    /// Replaces the references in a field with copies of their definitions. A
+    /// reference to a type that is already being expanded is left in place.
+    pub fn expand(&self, field: &Field) -> Field {
+        self.expand_within(field, &mut Vec::new())
+    }
+
+    fn expand_within(&self, field: &Field, expanding: &mut Vec<String>) -> Field {
+        let mut field = field.clone();
+        match &mut field {
+            Field::Primitive(prim) => match &mut prim.typ {
+                Typ::Ref { path, .. } if !expanding.contains(path) => {
+                    let Some(definition) = self.definition(path) else {
+                        return field;
+                    };
+                    let mut expanded = self.expand_within(definition, expanding);
+                    *expanded.meta_mut() = prim.meta.clone();
+                    return expanded;
+                }
+                Typ::Vec(inner) | Typ::Array(inner, _) | Typ::Set(inner) => {
+                    **inner = self.expand_within(inner, expanding)
+                }
+                Typ::Map(key, value) => {
+                    **key = self.expand_within(key, expanding);
+                    **value = self.expand_within(value, expanding);
+                }
+                Typ::Tuple(fields) => {
+                    for nested in fields {
+                        *nested = self.expand_within(nested, expanding);
+                    }
+                }
+                _ => {}
+            },
+            Field::Container(container) => {
+                expanding.push(container.path.clone());
+                for nested in &mut container.fields {
+                    *nested = self.expand_within(nested, expanding);
+                }
+                expanding.pop();
+            }
+            Field::Enum(enum_type) => {
+                expanding.push(enum_type.path.clone());
+                for variant in &mut enum_type.variants {
+                    if let VariantFields::Unnamed(fields) | VariantFields::Named(fields) =
+                        &mut variant.fields
+                    {
+                        for nested in fields {
+                            *nested = self.expand_within(nested, expanding);
+                        }
+                    }
+                }
+                expanding.pop();
+            }
+        }
+        field
+    }
+
+    /// Defines a container or enum, unless it is already defined, returning
+    /// the index of its definition.
+    fn define(&mut self, field: &Field) -> usize {
+        let path = match field {
+            Field::Container(container) => &container.path,
+            Field::Enum(enum_type) => &enum_type.path,
+            Field::Primitive(_) => unreachable!(),
+        };
+        if let Some(&index) = self.index.get(path) {
+            return index;
+        }
+        // Register the type before its fields, so that it is defined once.
+        let index = self.definitions.len();
+        self.index.insert(path.clone(), index);
+        self.definitions.push(field.clone());
+
+        let mut definition = field.clone();
+        match &mut definition {
+            Field::Container(container) => {
+                for nested in &mut container.fields {
+                    *nested = self.reference(nested);
+                }
+            }
+            Field::Enum(enum_type) => {
+                for variant in &mut enum_type.variants {
+                    if let VariantFields::Unnamed(fields) | VariantFields::Named(fields) =
+                        &mut variant.fields
+                    {
+                        for nested in fields {
+                            *nested = self.reference(nested);
+                        }
+                    }
+                }
+            }
+            Field::Primitive(_) => unreachable!(),
+        }
+        self.definitions[index] = definition;
+        index
+    }
+
+    /// Defines the named types a field uses, returning the field with them
+    /// replaced by references.
+    fn reference(&mut self, field: &Field) -> Field {
+        let (path, type_name) = match field {
+            Field::Container(container) => (&container.path, &container.type_name),
+            Field::Enum(enum_type) => (&enum_type.path, &enum_type.type_name),
+            Field::Primitive(prim) => {
+                let mut prim = prim.clone();
+                match &mut prim.typ {
+                    Typ::Vec(inner) | Typ::Array(inner, _) | Typ::Set(inner) => {
+                        **inner = self.reference(inner)
+                    }
+                    Typ::Map(key, value) => {
+                        **key = self.reference(key);
+                        **value = self.reference(value);
+                    }
+                    Typ::Tuple(fields) => {
+                        for nested in fields {
+                            *nested = self.reference(nested);
+                        }
+                    }
+                    _ => {}
+                }
+                return Field::Primitive(prim);
+            }
+        };
+        self.define(field);
+        Field::Primitive(Primitive {
+            meta: field.meta().clone(),
+            typ: Typ::Ref {
+                path: path.clone(),
+                type_name: type_name.clone(),
+            },
+            type_name: None,
+        })
+    }
+}
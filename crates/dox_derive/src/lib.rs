@@ -1,7 +1,8 @@
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{
-    parse_macro_input, Attribute, Data, DeriveInput, Expr, ExprLit, Fields, FieldsNamed, Lit, Meta,
+    parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput, Expr, ExprLit, Fields,
+    FieldsNamed, Lit, Meta,
 };
 
 fn extract_doc_comments(attrs: &[Attribute]) -> String {
@@ -26,97 +27,212 @@ fn extract_doc_comments(attrs: &[Attribute]) -> String {
         .join("\n")
 }
 
-fn extract_serde_rename(attrs: &[Attribute]) -> Option<String> {
-    attrs.iter().find_map(|attr| {
-        if attr.path().is_ident("serde") {
-            attr.parse_args_with(|input: syn::parse::ParseStream| {
-                let mut rename = None;
-                while !input.is_empty() {
-                    let meta: syn::Meta = input.parse()?;
-                    if let syn::Meta::NameValue(name_value) = meta {
-                        if name_value.path.is_ident("rename") {
-                            if let Expr::Lit(ExprLit {
-                                lit: Lit::Str(lit_str),
-                                ..
-                            }) = name_value.value
-                            {
-                                rename = Some(lit_str.value());
-                            }
-                        }
+/// A name or renaming rule, which serde lets differ between serializing and
+/// deserializing.
+#[derive(Clone, Default, PartialEq, Debug)]
+struct Names {
+    serialize: Option<String>,
+    deserialize: Option<String>,
+}
+
+/// The directions in which serde skips a field or variant.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+struct Skip {
+    serializing: bool,
+    deserializing: bool,
+}
+
+/// A `#[serde(default)]` or `#[serde(default = "path")]` attribute.
+enum SerdeDefault {
+    Trait,
+    Path(String),
+}
+
+/// Options from `#[serde(...)]` attributes, parsed once for a type, field or
+/// variant. Every serde key is recognized; keys that change how a value is
+/// written in ways dox can't follow are collected as warnings.
+#[derive(Default)]
+struct SerdeAttrs {
+    rename: Names,
+    rename_all: Names,
+    rename_all_fields: Names,
+    aliases: Vec<String>,
+    skip: Skip,
+    default: Option<SerdeDefault>,
+    flatten: bool,
+    transparent: bool,
+    deny_unknown_fields: bool,
+    untagged: bool,
+    tag: Option<String>,
+    content: Option<String>,
+    other: bool,
+    /// The keys dox ignores although they change the documented shape, with
+    /// the spans to report them at.
+    unsupported: Vec<(String, proc_macro2::Span)>,
+}
+
+impl SerdeAttrs {
+    fn parse(attrs: &[Attribute], target: Target) -> syn::Result<Self> {
+        let mut serde = SerdeAttrs::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                let key = meta
+                    .path
+                    .get_ident()
+                    .map(|ident| ident.to_string())
+                    .unwrap_or_default();
+                let targets: &[Target] = match key.as_str() {
+                    "rename" | "rename_all" | "bound" => {
+                        &[Target::Type, Target::Field, Target::Variant]
                     }
-                    if !input.is_empty() {
-                        input.parse::<syn::Token![,]>()?;
+                    "alias" | "skip" | "skip_serializing" | "skip_deserializing" | "with"
+                    | "serialize_with" | "deserialize_with" | "borrow" => {
+                        &[Target::Field, Target::Variant]
                     }
+                    "default" => &[Target::Type, Target::Field],
+                    "untagged" => &[Target::Type, Target::Variant],
+                    "rename_all_fields"
+                    | "deny_unknown_fields"
+                    | "tag"
+                    | "content"
+                    | "remote"
+                    | "transparent"
+                    | "from"
+                    | "try_from"
+                    | "into"
+                    | "crate"
+                    | "expecting"
+                    | "variant_identifier"
+                    | "field_identifier" => &[Target::Type],
+                    "flatten" | "skip_serializing_if" | "getter" => &[Target::Field],
+                    "other" => &[Target::Variant],
+                    _ => return Err(meta.error("dox: unknown serde attribute")),
+                };
+                if !targets.contains(&target) {
+                    return Err(meta.error(format!(
+                        "dox: serde `{}` is not supported on {}s",
+                        key,
+                        format!("{:?}", target).to_lowercase()
+                    )));
                 }
-                Ok(rename)
-            })
-            .ok()
-            .flatten()
-        } else {
-            None
-        }
-    })
-}
-
-fn extract_serde_rename_all(attrs: &[Attribute]) -> Option<String> {
-    attrs.iter().find_map(|attr| {
-        if attr.path().is_ident("serde") {
-            attr.parse_args_with(|input: syn::parse::ParseStream| {
-                let mut rename_all = None;
-                while !input.is_empty() {
-                    let meta: syn::Meta = input.parse()?;
-                    if let syn::Meta::NameValue(name_value) = meta {
-                        if name_value.path.is_ident("rename_all") {
-                            if let Expr::Lit(ExprLit {
-                                lit: Lit::Str(lit_str),
-                                ..
-                            }) = name_value.value
-                            {
-                                rename_all = Some(lit_str.value());
-                            }
-                        }
+                match key.as_str() {
+                    "rename" => serde.rename = parse_names(&meta)?,
+                    "rename_all" => serde.rename_all = parse_names(&meta)?,
+                    "rename_all_fields" => serde.rename_all_fields = parse_names(&meta)?,
+                    "alias" => serde.aliases.push(parse_str(&meta)?),
+                    "skip" => {
+                        serde.skip.serializing = true;
+                        serde.skip.deserializing = true;
                     }
-                    if !input.is_empty() {
-                        input.parse::<syn::Token![,]>()?;
+                    "skip_serializing" => serde.skip.serializing = true,
+                    "skip_deserializing" => serde.skip.deserializing = true,
+                    "default" if meta.input.peek(syn::Token![=]) => {
+                        serde.default = Some(SerdeDefault::Path(parse_str(&meta)?))
                     }
+                    "default" => serde.default = Some(SerdeDefault::Trait),
+                    "flatten" => serde.flatten = true,
+                    "transparent" => serde.transparent = true,
+                    "deny_unknown_fields" => serde.deny_unknown_fields = true,
+                    "untagged" if target == Target::Type => serde.untagged = true,
+                    "tag" => serde.tag = Some(parse_str(&meta)?),
+                    "content" => serde.content = Some(parse_str(&meta)?),
+                    "other" => serde.other = true,
+                    // These change how a value is read, which the documentation of
+                    // its type can't reflect.
+                    "with" | "deserialize_with" | "from" | "try_from" | "remote" | "untagged" => {
+                        serde.unsupported.push((key, meta.path.span()));
+                        skip_value(&meta)?;
+                    }
+                    // These only affect serializing, bounds and error messages, or
+                    // are described elsewhere.
+                    _ => skip_value(&meta)?,
                 }
-                Ok(rename_all)
-            })
-            .ok()
-            .flatten()
-        } else {
-            None
+                Ok(())
+            })?;
         }
-    })
-}
+        Ok(serde)
+    }
 
-fn extract_serde_skip(attrs: &[Attribute]) -> bool {
-    attrs.iter().any(|attr| {
-        if attr.path().is_ident("serde") {
-            attr.parse_args_with(|input: syn::parse::ParseStream| {
-                while !input.is_empty() {
-                    let meta: syn::Meta = input.parse().unwrap_or_else(|_| {
-                        syn::Meta::Path(syn::Path::from(syn::Ident::new(
-                            "",
-                            proc_macro2::Span::call_site(),
-                        )))
-                    });
-                    if let syn::Meta::Path(path) = meta {
-                        if path.is_ident("skip") {
-                            return Ok(true);
-                        }
-                    }
-                    if !input.is_empty() {
-                        let _ = input.parse::<syn::Token![,]>();
-                    }
+    /// The serde representation of an enum.
+    fn representation(&self) -> proc_macro2::TokenStream {
+        if self.untagged {
+            return quote! { dox::Representation::Untagged };
+        }
+        match (&self.tag, &self.content) {
+            (Some(tag), Some(content)) => quote! {
+                dox::Representation::Adjacent {
+                    tag: #tag.to_string(),
+                    content: #content.to_string(),
                 }
-                Ok(false)
-            })
-            .unwrap_or(false)
+            },
+            (Some(tag), None) => quote! {
+                dox::Representation::Internal { tag: #tag.to_string() }
+            },
+            _ => quote! { dox::Representation::External },
+        }
+    }
+
+    /// Warnings for the keys dox can't follow. Stable proc macros can't emit
+    /// warnings directly, so each one is the use of a deprecated item.
+    fn warnings(&self) -> proc_macro2::TokenStream {
+        let warnings = self.unsupported.iter().map(|(key, span)| {
+            let note = format!(
+                "dox: serde `{}` changes how this value is read, which dox can't document; \
+                 its documentation shows the Rust type instead",
+                key
+            );
+            quote::quote_spanned! {*span=>
+                const _: () = {
+                    #[deprecated(note = #note)]
+                    struct DoxUnsupported;
+                    let _ = DoxUnsupported;
+                };
+            }
+        });
+        quote! { #(#warnings)* }
+    }
+}
+
+/// Parses the string value of `key = "..."`.
+fn parse_str(meta: &syn::meta::ParseNestedMeta) -> syn::Result<String> {
+    Ok(meta.value()?.parse::<syn::LitStr>()?.value())
+}
+
+/// Parses `key = "..."`, which applies in both directions, or
+/// `key(serialize = "...", deserialize = "...")`.
+fn parse_names(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Names> {
+    if meta.input.peek(syn::Token![=]) {
+        let value = parse_str(meta)?;
+        return Ok(Names {
+            serialize: Some(value.clone()),
+            deserialize: Some(value),
+        });
+    }
+    let mut names = Names::default();
+    meta.parse_nested_meta(|nested| {
+        if nested.path.is_ident("serialize") {
+            names.serialize = Some(parse_str(&nested)?);
+        } else if nested.path.is_ident("deserialize") {
+            names.deserialize = Some(parse_str(&nested)?);
         } else {
-            false
+            return Err(nested.error("dox: expected `serialize` or `deserialize`"));
         }
-    })
+        Ok(())
+    })?;
+    Ok(names)
+}
+
+/// Consumes the value of a key dox doesn't use, whether it is `key`,
+/// `key = value` or `key(...)`.
+fn skip_value(meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        content.parse::<proc_macro2::TokenStream>()?;
+    }
+    Ok(())
 }
 
 fn rename_field(name: &str, rename_rule: &str) -> String {
@@ -166,131 +282,584 @@ fn rename_field(name: &str, rename_rule: &str) -> String {
     }
 }
 
-fn process_field(field: &syn::Field, rename_all: &Option<String>) -> proc_macro2::TokenStream {
-    if extract_serde_skip(&field.attrs) {
-        return quote! {};
+/// Where a `#[dox(...)]` attribute appears, which decides the options it accepts.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Target {
+    Type,
+    Field,
+    Variant,
+}
+
+/// Options from `#[dox(...)]` attributes, which take precedence over serde's.
+#[derive(Default)]
+struct DoxAttrs {
+    skip: bool,
+    hidden: bool,
+    rename: Option<String>,
+    doc: Option<String>,
+    /// An expression building the example's `serde_json::Value`.
+    example: Option<proc_macro2::TokenStream>,
+    type_name: Option<String>,
+}
+
+impl DoxAttrs {
+    fn parse(attrs: &[Attribute], target: Target) -> syn::Result<Self> {
+        let mut dox = DoxAttrs::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("dox")) {
+            attr.parse_nested_meta(|meta| {
+                let option = meta
+                    .path
+                    .get_ident()
+                    .map(|ident| ident.to_string())
+                    .unwrap_or_default();
+                let targets: &[Target] = match option.as_str() {
+                    "skip" | "hidden" => &[Target::Field, Target::Variant],
+                    "rename" | "doc" => &[Target::Type, Target::Field, Target::Variant],
+                    "example" => &[Target::Field],
+                    "type_name" => &[Target::Type],
+                    _ => return Err(meta.error("dox: unknown option")),
+                };
+                if !targets.contains(&target) {
+                    return Err(meta.error(format!(
+                        "dox: `{}` is not supported on {}s",
+                        option,
+                        format!("{:?}", target).to_lowercase()
+                    )));
+                }
+                match option.as_str() {
+                    "skip" => dox.skip = true,
+                    "hidden" => dox.hidden = true,
+                    "rename" => dox.rename = Some(meta.value()?.parse::<syn::LitStr>()?.value()),
+                    "doc" => dox.doc = Some(meta.value()?.parse::<syn::LitStr>()?.value()),
+                    "type_name" => {
+                        dox.type_name = Some(meta.value()?.parse::<syn::LitStr>()?.value())
+                    }
+                    "example" => dox.example = Some(parse_example(meta.value()?)?),
+                    _ => unreachable!(),
+                }
+                Ok(())
+            })?;
+        }
+        Ok(dox)
+    }
+}
+
+/// Parses an example given as a string, number or boolean literal.
+fn parse_example(input: syn::parse::ParseStream) -> syn::Result<proc_macro2::TokenStream> {
+    let negative = input.parse::<Option<syn::Token![-]>>()?.is_some();
+    let lit: Lit = input.parse()?;
+    let value = match &lit {
+        Lit::Str(s) if !negative => quote! { #s },
+        Lit::Bool(b) if !negative => quote! { #b },
+        Lit::Int(i) => {
+            let value = i.base10_parse::<i64>()?;
+            let value = if negative { -value } else { value };
+            quote! { #value }
+        }
+        Lit::Float(f) => {
+            let value = f.base10_parse::<f64>()?;
+            let value = if negative { -value } else { value };
+            quote! { #value }
+        }
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &lit,
+                "dox: expected a string, number or boolean example",
+            ))
+        }
+    };
+    Ok(quote! { dox::__private::Value::from(#value) })
+}
+
+/// Builds the `dox::DefaultValue` for a default, given the type whose
+/// `Default` impl a `#[serde(default)]` uses, and the member of its default
+/// instance that holds the field's default, for a container's default.
+fn default_value(
+    default: &SerdeDefault,
+    trait_type: proc_macro2::TokenStream,
+    member: Option<&syn::Ident>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let member = member.map(|member| quote! { .#member });
+    let (kind, value) = match default {
+        // The type may only implement `Default` for some of its type
+        // parameters, so the instance is only built if it does.
+        SerdeDefault::Trait => (
+            quote! { dox::DefaultKind::Trait },
+            quote! {
+                (&dox::__private::InstanceProbe::<#trait_type, _>::new(|instance: #trait_type| {
+                    (&dox::__private::DefaultProbe(&instance #member)).default_value()
+                }))
+                .default_value()
+            },
+        ),
+        SerdeDefault::Path(path) => {
+            let func: syn::ExprPath = syn::parse_str(path)?;
+            (
+                quote! { dox::DefaultKind::Path(#path.to_string()) },
+                quote! { (&dox::__private::DefaultProbe(&#func() #member)).default_value() },
+            )
+        }
+    };
+    Ok(quote! {
+        dox::DefaultValue {
+            kind: #kind,
+            value: {
+                #[allow(unused_imports)]
+                use dox::__private::{
+                    DefaultInstance as _, OpaqueDefault as _, OpaqueInstance as _,
+                    SerializeDefault as _,
+                };
+                #value
+            },
+        }
+    })
+}
+
+/// The names a field or variant is read and written under. `#[dox(rename)]` wins in both
+/// directions, then serde's `rename`, then its `rename_all` rule.
+fn resolve_names(
+    ident: &str,
+    dox_rename: Option<&str>,
+    rename: &Names,
+    rename_all: &Names,
+) -> (String, String) {
+    let resolve = |rename: &Option<String>, rule: &Option<String>| {
+        dox_rename
+            .map(str::to_string)
+            .or_else(|| rename.clone())
+            .or_else(|| rule.as_ref().map(|rule| rename_field(ident, rule)))
+            .unwrap_or_else(|| ident.to_string())
+    };
+    (
+        resolve(&rename.deserialize, &rename_all.deserialize),
+        resolve(&rename.serialize, &rename_all.serialize),
+    )
+}
+
+/// A field's generated `dox::Field` expression, or the errors in its attributes.
+struct ProcessedField {
+    tokens: proc_macro2::TokenStream,
+    /// Whether the field is spliced into its parent by `#[serde(flatten)]`.
+    flatten: bool,
+}
+
+impl ProcessedField {
+    fn error(err: syn::Error) -> Self {
+        ProcessedField {
+            tokens: err.to_compile_error(),
+            flatten: false,
+        }
+    }
+}
+
+fn process_field(
+    field: &syn::Field,
+    rename_all: &Names,
+    container_default: Option<&SerdeDefault>,
+) -> Option<ProcessedField> {
+    let dox = match DoxAttrs::parse(&field.attrs, Target::Field) {
+        Ok(dox) => dox,
+        Err(err) => return Some(ProcessedField::error(err)),
+    };
+    let serde = match SerdeAttrs::parse(&field.attrs, Target::Field) {
+        Ok(serde) => serde,
+        Err(err) => return Some(ProcessedField::error(err)),
+    };
+    let skip = serde.skip;
+    if dox.skip || (skip.serializing && skip.deserializing) {
+        return None;
     }
 
-    let name = field.ident.as_ref().unwrap();
-    let docs = extract_doc_comments(&field.attrs);
+    let name = field
+        .ident
+        .as_ref()
+        .map(|ident| ident.to_string())
+        .unwrap_or_default();
+    let docs = dox
+        .doc
+        .unwrap_or_else(|| extract_doc_comments(&field.attrs));
     let ty = &field.ty;
-    let name_str = extract_serde_rename(&field.attrs)
-        .or_else(|| {
-            rename_all
-                .as_ref()
-                .map(|rule| rename_field(&name.to_string(), rule))
-        })
-        .unwrap_or_else(|| name.to_string());
+    let (input_name, output_name) =
+        resolve_names(&name, dox.rename.as_deref(), &serde.rename, rename_all);
+    let set_serialized_name = (output_name != input_name).then(|| {
+        quote! { field.set_serialized_name(Some(#output_name.to_string())); }
+    });
+    let aliases = &serde.aliases;
+    let set_aliases = (!aliases.is_empty()).then(|| {
+        quote! { field.set_aliases(vec![#(#aliases.to_string()),*]); }
+    });
 
-    quote! {
+    // Undocumented tuple fields keep the documentation of their type, and so
+    // do undocumented named fields whose type is a newtype.
+    let set_doc = if !docs.is_empty() {
+        quote! { field.set_doc(#docs); }
+    } else if field.ident.is_some() {
+        quote! {
+            if !matches!(&field, dox::Field::Primitive(dox::Primitive { type_name: Some(_), .. })) {
+                field.set_doc("");
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // A field's own default wins over one declared on its container.
+    let default = match (&serde.default, container_default) {
+        (Some(default), _) => Some(default_value(default, quote! { #ty }, None)),
+        (None, Some(default)) => field
+            .ident
+            .as_ref()
+            .map(|ident| default_value(default, quote! { Self }, Some(ident))),
+        (None, None) => None,
+    };
+    let set_default = match default {
+        Some(Ok(default)) => quote! { field.set_default(Some(#default)); },
+        Some(Err(err)) => err.to_compile_error(),
+        None => quote! {},
+    };
+
+    let set_hidden = if dox.hidden {
+        quote! { field.set_hidden(true); }
+    } else {
+        quote! {}
+    };
+    let set_example = dox.example.map(|example| {
+        quote! { field.set_example(Some(#example)); }
+    });
+    let set_direction = match (skip.serializing, skip.deserializing) {
+        (true, false) => Some(quote! { field.set_direction(dox::Direction::Input); }),
+        (false, true) => Some(quote! { field.set_direction(dox::Direction::Output); }),
+        _ => None,
+    };
+
+    let warnings = serde.warnings();
+
+    let tokens = quote! {
         {
+            #warnings
             let mut field = <#ty as dox::Dox>::dox();
-            match &mut field {
-                dox::Field::Container(container) => {
-                    container.name = #name_str.to_string();
-                    container.doc = #docs.to_string();
-                },
-                dox::Field::Primitive(primitive) => {
-                    primitive.name = #name_str.to_string();
-                    primitive.doc = #docs.to_string();
-                },
-                dox::Field::Enum(enum_type) => {
-                    enum_type.name = #name_str.to_string();
-                    enum_type.doc = #docs.to_string();
-                },
-            }
+            field.set_name(#input_name);
+            #set_serialized_name
+            #set_aliases
+            #set_doc
+            #set_default
+            #set_hidden
+            #set_example
+            #set_direction
             field
         }
+    };
+    Some(ProcessedField {
+        tokens,
+        flatten: serde.flatten,
+    })
+}
+
+/// Builds an expression evaluating to the `Vec<dox::Field>` for a set of named fields,
+/// splicing in the fields of any `#[serde(flatten)]` members.
+fn process_named_fields<'a>(
+    fields: impl IntoIterator<Item = &'a syn::Field>,
+    rename_all: &Names,
+    container_default: Option<&SerdeDefault>,
+) -> proc_macro2::TokenStream {
+    let pushes = fields.into_iter().filter_map(|f| {
+        let ProcessedField {
+            tokens: field,
+            flatten,
+        } = process_field(f, rename_all, container_default)?;
+        Some(if flatten {
+            quote! { fields.extend(#field.into_flattened()); }
+        } else {
+            quote! { fields.push(#field); }
+        })
+    });
+    quote! {
+        {
+            let mut fields = Vec::new();
+            #(#pushes)*
+            fields
+        }
     }
 }
 
 fn process_enum_variant(
     variant: &syn::Variant,
-    rename_all: &Option<String>,
+    rename_all: &Names,
+    rename_all_fields: &Names,
+) -> Option<proc_macro2::TokenStream> {
+    let dox = match DoxAttrs::parse(&variant.attrs, Target::Variant) {
+        Ok(dox) => dox,
+        Err(err) => return Some(err.to_compile_error()),
+    };
+    let serde = match SerdeAttrs::parse(&variant.attrs, Target::Variant) {
+        Ok(serde) => serde,
+        Err(err) => return Some(err.to_compile_error()),
+    };
+    // A variant that can't be deserialized can't appear in a config.
+    if dox.skip || serde.skip.deserializing {
+        return None;
+    }
+
+    let (input_name, output_name) = resolve_names(
+        &variant.ident.to_string(),
+        dox.rename.as_deref(),
+        &serde.rename,
+        rename_all,
+    );
+    let serialized_name = if output_name != input_name {
+        quote! { Some(#output_name.to_string()) }
+    } else {
+        quote! { None }
+    };
+    let aliases = &serde.aliases;
+    let other = serde.other;
+    let docs = dox
+        .doc
+        .unwrap_or_else(|| extract_doc_comments(&variant.attrs));
+    let hidden = dox.hidden;
+
+    // Serde applies a variant's own `rename_all` to the fields of a struct
+    // variant, and otherwise the enum's `rename_all_fields`.
+    let fields_rename_all = if serde.rename_all == Names::default() {
+        rename_all_fields
+    } else {
+        &serde.rename_all
+    };
+    let fields = match &variant.fields {
+        Fields::Unit => quote! { dox::VariantFields::Unit },
+        Fields::Unnamed(fields) => {
+            let fields: Vec<_> = fields
+                .unnamed
+                .iter()
+                .filter_map(|f| process_field(f, &Names::default(), None))
+                .map(|f| f.tokens)
+                .collect();
+            quote! { dox::VariantFields::Unnamed(vec![#(#fields),*]) }
+        }
+        Fields::Named(fields) => {
+            let fields = process_named_fields(&fields.named, fields_rename_all, None);
+            quote! { dox::VariantFields::Named(#fields) }
+        }
+    };
+
+    let warnings = serde.warnings();
+
+    Some(quote! {
+        {
+            #warnings
+            dox::Variant {
+                name: #input_name.to_string(),
+                doc: #docs.to_string(),
+                fields: #fields,
+                hidden: #hidden,
+                aliases: vec![#(#aliases.to_string()),*],
+                serialized_name: #serialized_name,
+                other: #other,
+            }
+        }
+    })
+}
+
+/// Whether a type mentions the type named `name`, or `Self`, anywhere within
+/// it, as `Tree` does in `Vec<Tree>`. A type of the same name in another
+/// module, such as `v1::Tree`, is not the same type.
+fn refers_to(ty: &syn::Type, name: &syn::Ident) -> bool {
+    fn walk(tokens: proc_macro2::TokenStream, name: &syn::Ident) -> bool {
+        let mut in_path = false;
+        tokens.into_iter().any(|token| {
+            let found = match &token {
+                proc_macro2::TokenTree::Ident(ident) => {
+                    !in_path && (ident == name || ident == "Self")
+                }
+                proc_macro2::TokenTree::Group(group) => walk(group.stream(), name),
+                _ => false,
+            };
+            in_path = matches!(&token, proc_macro2::TokenTree::Punct(p) if p.as_char() == ':');
+            found
+        })
+    }
+    walk(quote! { #ty }, name)
+}
+
+/// Builds the field for a struct that is written as a single value, such as a
+/// tuple struct or a unit struct.
+fn primitive_struct(
+    name: &str,
+    docs: &str,
+    typ: proc_macro2::TokenStream,
 ) -> proc_macro2::TokenStream {
-    let variant_name = &variant.ident;
-    let variant_str = variant_name.to_string();
-    let renamed_variant = rename_all
-        .as_ref()
-        .map(|rule| rename_field(&variant_str, rule))
-        .unwrap_or_else(|| variant_str.clone());
-    let docs = extract_doc_comments(&variant.attrs);
+    let meta = field_meta(name, docs);
+    quote! {
+        dox::Field::Primitive(dox::Primitive {
+            meta: #meta,
+            typ: #typ,
+            type_name: None,
+        })
+    }
+}
 
+/// Builds the `dox::FieldMeta` of a derived type, which has a name and
+/// documentation, and otherwise the defaults.
+fn field_meta(name: &str, docs: &str) -> proc_macro2::TokenStream {
     quote! {
-        dox::Variant {
-            name: #renamed_variant.to_string(),
+        dox::FieldMeta {
+            name: #name.to_string(),
             doc: #docs.to_string(),
+            ..::core::default::Default::default()
         }
     }
 }
 
-#[proc_macro_derive(Dox)]
+#[proc_macro_derive(Dox, attributes(dox))]
 pub fn dox_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
+    let dox = match DoxAttrs::parse(&input.attrs, Target::Type) {
+        Ok(dox) => dox,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let serde = match SerdeAttrs::parse(&input.attrs, Target::Type) {
+        Ok(serde) => serde,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let warnings = serde.warnings();
+    let name_str = dox.rename.unwrap_or_else(|| name.to_string());
+
+    // Each type parameter must itself be documented, and is shown in the type
+    // name of the instantiation, e.g. `Page<User>`.
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(dox::Dox));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let type_params: Vec<_> = input.generics.type_params().map(|p| &p.ident).collect();
+    let type_name = match dox.type_name {
+        Some(type_name) => quote! { #type_name.to_string() },
+        None if type_params.is_empty() => {
+            let type_name = name.to_string();
+            quote! { #type_name.to_string() }
+        }
+        None => {
+            let base = name.to_string();
+            quote! {
+                format!(
+                    "{}<{}>",
+                    #base,
+                    [#(<#type_params as dox::Dox>::dox().type_name()),*].join(", ")
+                )
+            }
+        }
+    };
+    let docs = dox
+        .doc
+        .unwrap_or_else(|| extract_doc_comments(&input.attrs));
 
     let expanded = match &input.data {
         Data::Struct(data_struct) => {
-            let fields = match &data_struct.fields {
-                Fields::Named(FieldsNamed { named, .. }) => named,
-                _ => {
-                    return syn::Error::new_spanned(&input, "dox: only named fields are supported")
+            let transparent = serde.transparent;
+            let deny_unknown_fields = serde.deny_unknown_fields;
+            let body = match &data_struct.fields {
+                Fields::Named(FieldsNamed { named, .. }) if !transparent => {
+                    let field_docs =
+                        process_named_fields(named, &serde.rename_all, serde.default.as_ref());
+                    let meta = field_meta(&name_str, &docs);
+                    quote! {
+                        dox::Field::Container(dox::Container {
+                            meta: #meta,
+                            type_name: type_name.clone(),
+                            path: path.to_string(),
+                            type_doc: #docs.to_string(),
+                            fields: #field_docs,
+                            deny_unknown_fields: #deny_unknown_fields,
+                        })
+                    }
+                }
+                Fields::Unit => primitive_struct(&name_str, &docs, quote! { dox::Typ::Unit }),
+                fields => {
+                    // A struct written as its fields has no definition that a
+                    // reference to itself could point to.
+                    if let Some(field) = fields.iter().find(|f| refers_to(&f.ty, name)) {
+                        return syn::Error::new_spanned(
+                            &field.ty,
+                            "dox: a tuple, newtype or transparent struct cannot contain itself; give it named fields",
+                        )
                         .to_compile_error()
-                        .into()
+                        .into();
+                    }
+                    let processed: Vec<_> = fields
+                        .iter()
+                        .filter_map(|f| process_field(f, &Names::default(), None))
+                        .map(|f| f.tokens)
+                        .collect();
+                    if transparent || fields.len() == 1 {
+                        // A newtype, or a transparent struct, is written as its one field.
+                        let Some(inner) = processed.first() else {
+                            return syn::Error::new_spanned(
+                                &input,
+                                "dox: a newtype or transparent struct needs a field that is not skipped",
+                            )
+                            .to_compile_error()
+                            .into();
+                        };
+                        let set_doc = (!docs.is_empty()).then(|| quote! { field.set_doc(#docs); });
+                        quote! {
+                            {
+                                let mut field = #inner;
+                                field.set_name(#name_str);
+                                if let dox::Field::Primitive(prim) = &mut field {
+                                    prim.type_name = Some(type_name.clone());
+                                }
+                                #set_doc
+                                field
+                            }
+                        }
+                    } else {
+                        primitive_struct(
+                            &name_str,
+                            &docs,
+                            quote! { dox::Typ::Tuple(vec![#(#processed),*]) },
+                        )
+                    }
                 }
             };
 
-            let rename_all = extract_serde_rename_all(&input.attrs);
-            let field_docs: Vec<_> = fields
-                .iter()
-                .map(|f| process_field(f, &rename_all))
-                .collect();
-
-            let struct_docs = extract_doc_comments(&input.attrs);
-            let name_str = name.to_string();
-
             quote! {
-                impl dox::Dox for #name {
+                impl #impl_generics dox::Dox for #name #ty_generics #where_clause {
                     fn dox() -> dox::Field {
-                        dox::Field::Container(dox::Container {
-                            name: #name_str.to_string(),
-                            type_name: stringify!(#name).to_string(),
-                            fields: vec![
-                                #(#field_docs),*
-                            ],
-                            doc: #struct_docs.to_string(),
-                        })
+                        #warnings
+                        let path = ::std::any::type_name::<Self>();
+                        let type_name: String = #type_name;
+                        dox::__private::build_once(path, &type_name, || #body)
                     }
                 }
             }
         }
         Data::Enum(data_enum) => {
-            let enum_docs = extract_doc_comments(&input.attrs);
-            let name_str = name.to_string();
-
-            let rename_all = extract_serde_rename_all(&input.attrs);
+            let representation = serde.representation();
             let variants: Vec<_> = data_enum
                 .variants
                 .iter()
-                .map(|v| {
-                    if !v.fields.is_empty() {
-                        return syn::Error::new_spanned(
-                            v,
-                            "dox: only primitive enums are supported",
-                        )
-                        .to_compile_error();
-                    }
-                    process_enum_variant(v, &rename_all)
+                .filter_map(|v| {
+                    process_enum_variant(v, &serde.rename_all, &serde.rename_all_fields)
                 })
                 .collect();
 
+            let meta = field_meta(&name_str, &docs);
             quote! {
-                impl dox::Dox for #name {
+                impl #impl_generics dox::Dox for #name #ty_generics #where_clause {
                     fn dox() -> dox::Field {
-                        dox::Field::Enum(dox::Enum {
-                            name: #name_str.to_string(),
-                            doc: #enum_docs.to_string(),
-                            variants: vec![#(#variants),*],
+                        #warnings
+                        let path = ::std::any::type_name::<Self>();
+                        let type_name: String = #type_name;
+                        dox::__private::build_once(path, &type_name, || {
+                            dox::Field::Enum(dox::Enum {
+                                meta: #meta,
+                                type_name: type_name.clone(),
+                                path: path.to_string(),
+                                type_doc: #docs.to_string(),
+                                variants: vec![#(#variants),*],
+                                representation: #representation,
+                            })
                         })
                     }
                 }
@@ -311,70 +880,216 @@ mod tests {
     use super::*;
     use syn::parse_quote;
 
+    fn names(serialize: &str, deserialize: &str) -> Names {
+        Names {
+            serialize: Some(serialize.to_string()),
+            deserialize: Some(deserialize.to_string()),
+        }
+    }
+
+    fn serde(attrs: &[Attribute], target: Target) -> SerdeAttrs {
+        SerdeAttrs::parse(attrs, target).unwrap()
+    }
+
     #[test]
-    fn test_extract_serde_rename() {
+    fn test_serde_rename() {
         let item: syn::ItemStruct = parse_quote! {
             #[serde(rename = "new_name")]
             struct Test;
         };
         assert_eq!(
-            extract_serde_rename(&item.attrs),
-            Some("new_name".to_string())
+            serde(&item.attrs, Target::Type).rename,
+            names("new_name", "new_name")
+        );
+
+        let item: syn::ItemStruct = parse_quote! {
+            #[serde(default, rename(serialize = "out", deserialize = "in"))]
+            struct Test;
+        };
+        assert_eq!(serde(&item.attrs, Target::Type).rename, names("out", "in"));
+
+        let item: syn::ItemStruct = parse_quote! {
+            #[serde(rename(deserialize = "in"))]
+            struct Test;
+        };
+        assert_eq!(
+            serde(&item.attrs, Target::Type).rename,
+            Names {
+                serialize: None,
+                deserialize: Some("in".to_string()),
+            }
         );
 
         let item: syn::ItemStruct = parse_quote! {
             #[derive(Debug)]
             struct Test;
         };
-        assert_eq!(extract_serde_rename(&item.attrs), None);
+        assert_eq!(serde(&item.attrs, Target::Type).rename, Names::default());
     }
 
     #[test]
-    fn test_extract_serde_rename_all() {
+    fn test_serde_rename_all() {
         let item: syn::ItemStruct = parse_quote! {
             #[serde(rename_all = "camelCase")]
             struct Test;
         };
         assert_eq!(
-            extract_serde_rename_all(&item.attrs),
-            Some("camelCase".to_string())
+            serde(&item.attrs, Target::Type).rename_all,
+            names("camelCase", "camelCase")
         );
 
         let item: syn::ItemStruct = parse_quote! {
-            #[serde(rename_all = "snake_case")]
+            #[serde(rename_all(serialize = "snake_case", deserialize = "kebab-case"))]
             struct Test;
         };
         assert_eq!(
-            extract_serde_rename_all(&item.attrs),
-            Some("snake_case".to_string())
+            serde(&item.attrs, Target::Type).rename_all,
+            names("snake_case", "kebab-case")
         );
 
-        let item: syn::ItemStruct = parse_quote! {
-            #[derive(Debug)]
-            struct Test;
+        let item: syn::ItemEnum = parse_quote! {
+            #[serde(tag = "type", rename_all_fields = "camelCase")]
+            enum Test {}
         };
-        assert_eq!(extract_serde_rename_all(&item.attrs), None);
+        let attrs = serde(&item.attrs, Target::Type);
+        assert_eq!(attrs.rename_all, Names::default());
+        assert_eq!(attrs.rename_all_fields, names("camelCase", "camelCase"));
     }
 
     #[test]
-    fn test_extract_serde_skip() {
-        let item: syn::ItemStruct = parse_quote! {
+    fn test_serde_alias() {
+        let field: syn::Field = parse_quote! {
+            #[serde(alias = "colour", alias = "hue")]
+            #[serde(alias = "tint")]
+            color: String
+        };
+        assert_eq!(
+            serde(&field.attrs, Target::Field).aliases,
+            vec!["colour", "hue", "tint"]
+        );
+    }
+
+    #[test]
+    fn test_serde_skip() {
+        let skip = |serializing, deserializing| Skip {
+            serializing,
+            deserializing,
+        };
+
+        let field: syn::Field = parse_quote! {
             #[serde(skip)]
-            struct Test;
+            value: i32
+        };
+        assert_eq!(serde(&field.attrs, Target::Field).skip, skip(true, true));
+
+        let field: syn::Field = parse_quote! {
+            #[serde(rename = "v", skip_serializing)]
+            value: i32
+        };
+        assert_eq!(serde(&field.attrs, Target::Field).skip, skip(true, false));
+
+        let field: syn::Field = parse_quote! {
+            #[serde(default, skip_deserializing)]
+            value: i32
+        };
+        assert_eq!(serde(&field.attrs, Target::Field).skip, skip(false, true));
+
+        let field: syn::Field = parse_quote! {
+            #[serde(skip_serializing_if = "Option::is_none", rename(serialize = "v"))]
+            value: Option<i32>
+        };
+        assert_eq!(serde(&field.attrs, Target::Field).skip, skip(false, false));
+    }
+
+    #[test]
+    fn test_serde_attrs() {
+        let field: syn::Field = parse_quote! {
+            #[serde(default = "default_port", rename = "p", flatten)]
+            port: u16
+        };
+        let attrs = serde(&field.attrs, Target::Field);
+        assert!(
+            matches!(attrs.default, Some(SerdeDefault::Path(ref path)) if path == "default_port")
+        );
+        assert_eq!(attrs.rename, names("p", "p"));
+        assert!(attrs.flatten);
+
+        let item: syn::ItemEnum = parse_quote! {
+            #[serde(tag = "t", content = "c", bound = "T: Clone", crate = "serde")]
+            enum Test {}
+        };
+        let attrs = serde(&item.attrs, Target::Type);
+        assert_eq!(attrs.tag.as_deref(), Some("t"));
+        assert_eq!(attrs.content.as_deref(), Some("c"));
+        assert!(attrs.unsupported.is_empty());
+
+        let field: syn::Field = parse_quote! {
+            #[serde(with = "humantime_serde", serialize_with = "ser", borrow)]
+            timeout: Duration
+        };
+        let unsupported: Vec<_> = serde(&field.attrs, Target::Field)
+            .unsupported
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(unsupported, vec!["with"]);
+
+        let variant: syn::Variant = parse_quote! {
+            #[serde(other)]
+            Unknown
+        };
+        assert!(serde(&variant.attrs, Target::Variant).other);
+    }
+
+    #[test]
+    fn test_serde_attrs_errors() {
+        let error = |attrs: &[Attribute], target| match SerdeAttrs::parse(attrs, target) {
+            Err(err) => err.to_string(),
+            Ok(_) => panic!("expected an error"),
         };
-        assert!(extract_serde_skip(&item.attrs));
 
         let item: syn::ItemStruct = parse_quote! {
-            #[serde(rename = "test")]
+            #[serde(colour)]
             struct Test;
         };
-        assert!(!extract_serde_skip(&item.attrs));
+        assert_eq!(
+            error(&item.attrs, Target::Type),
+            "dox: unknown serde attribute"
+        );
 
         let item: syn::ItemStruct = parse_quote! {
-            #[derive(Debug)]
+            #[serde(flatten)]
             struct Test;
         };
-        assert!(!extract_serde_skip(&item.attrs));
+        assert_eq!(
+            error(&item.attrs, Target::Type),
+            "dox: serde `flatten` is not supported on types"
+        );
+
+        // A bad value stops parsing, rather than hiding the keys after it.
+        let field: syn::Field = parse_quote! {
+            #[serde(default = 3, rename = "x")]
+            value: i32
+        };
+        assert_eq!(
+            error(&field.attrs, Target::Field),
+            "expected string literal"
+        );
+
+        let field: syn::Field = parse_quote! {
+            #[serde(rename(input = "x"))]
+            value: i32
+        };
+        assert_eq!(
+            error(&field.attrs, Target::Field),
+            "dox: expected `serialize` or `deserialize`"
+        );
+
+        let field: syn::Field = parse_quote! {
+            #[serde(skip = )]
+            value: i32
+        };
+        assert!(SerdeAttrs::parse(&field.attrs, Target::Field).is_err());
     }
 
     #[test]
@@ -397,6 +1112,19 @@ mod tests {
         assert_eq!(extract_doc_comments(&item.attrs), "");
     }
 
+    #[test]
+    fn test_refers_to() {
+        let name: syn::Ident = parse_quote!(Tree);
+        let refers = |ty: syn::Type| refers_to(&ty, &name);
+        assert!(refers(parse_quote!(Tree)));
+        assert!(refers(parse_quote!(Vec<Tree>)));
+        assert!(refers(parse_quote!(Option<Box<Self>>)));
+        assert!(refers(parse_quote!([(u32, Tree); 2])));
+        assert!(!refers(parse_quote!(Vec<Trees>)));
+        assert!(!refers(parse_quote!(v1::Tree)));
+        assert!(!refers(parse_quote!(HashMap<String, u32>)));
+    }
+
     #[test]
     fn test_rename_field() {
         assert_eq!(rename_field("test_field", "lowercase"), "test_field");
@@ -420,4 +1148,70 @@ mod tests {
             "TEST_FIELD"
         );
     }
+
+    #[test]
+    fn test_dox_attrs() {
+        let field: syn::Field = parse_quote! {
+            #[dox(rename = "port", doc = "The port", hidden, example = -1)]
+            #[dox(skip)]
+            value: i32
+        };
+        let dox = DoxAttrs::parse(&field.attrs, Target::Field).unwrap();
+        assert!(dox.skip);
+        assert!(dox.hidden);
+        assert_eq!(dox.rename, Some("port".to_string()));
+        assert_eq!(dox.doc, Some("The port".to_string()));
+        assert_eq!(
+            dox.example.unwrap().to_string(),
+            quote! { dox::__private::Value::from(-1i64) }.to_string()
+        );
+
+        let item: syn::ItemStruct = parse_quote! {
+            #[dox(type_name = "Settings")]
+            struct Test;
+        };
+        let dox = DoxAttrs::parse(&item.attrs, Target::Type).unwrap();
+        assert_eq!(dox.type_name, Some("Settings".to_string()));
+    }
+
+    #[test]
+    fn test_dox_attrs_errors() {
+        let error = |attrs: &[Attribute], target| match DoxAttrs::parse(attrs, target) {
+            Err(err) => err.to_string(),
+            Ok(_) => panic!("expected an error"),
+        };
+
+        let item: syn::ItemStruct = parse_quote! {
+            #[dox(colour)]
+            struct Test;
+        };
+        assert_eq!(error(&item.attrs, Target::Type), "dox: unknown option");
+
+        let item: syn::ItemStruct = parse_quote! {
+            #[dox(skip)]
+            struct Test;
+        };
+        assert_eq!(
+            error(&item.attrs, Target::Type),
+            "dox: `skip` is not supported on types"
+        );
+
+        let field: syn::Field = parse_quote! {
+            #[dox(rename = 3)]
+            value: i32
+        };
+        assert_eq!(
+            error(&field.attrs, Target::Field),
+            "expected string literal"
+        );
+
+        let field: syn::Field = parse_quote! {
+            #[dox(example = b"bytes")]
+            value: i32
+        };
+        assert_eq!(
+            error(&field.attrs, Target::Field),
+            "dox: expected a string, number or boolean example"
+        );
+    }
 }
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 pub enum EnumVariants {
     #[default]
     Plain,
-    // Compound(Vec<String>),
+    Compound(Vec<String>),
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Dox)]
@@ -20,6 +20,9 @@ pub struct Demo {
     /// The default dialect.
     #[serde(default)]
     pub plain_variants: EnumVariants,
+
+    /// An optional limit
+    pub limit: Option<u32>,
 }
 
 fn main() {
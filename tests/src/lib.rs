@@ -2,7 +2,15 @@
 #![allow(clippy::enum_variant_names)]
 
 use dox::Dox;
-use serde::Serialize;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, SocketAddr};
+use std::num::NonZeroU32;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 #[derive(Dox, Serialize)]
 struct InnerStruct {
@@ -24,6 +32,449 @@ struct TestStruct {
     vector: Vec<i32>,
 }
 
+/// A listening socket
+#[derive(Dox, Serialize)]
+struct Listener {
+    /// The port to bind
+    port: u16,
+}
+
+/// Log verbosity
+#[derive(Dox, Serialize)]
+enum Level {
+    /// Only errors
+    Error,
+    /// Everything
+    Debug,
+}
+
+/// A struct holding vectors of structs and enums
+#[derive(Dox, Serialize)]
+struct VecStruct {
+    /// Sockets to listen on
+    listeners: Vec<Listener>,
+    /// Enabled levels
+    levels: Vec<Level>,
+}
+
+/// A struct with optional fields
+#[derive(Dox, Serialize)]
+struct OptionStruct {
+    /// An optional string
+    name: Option<String>,
+    /// An optional nested struct
+    listener: Option<Listener>,
+    /// A required integer
+    count: u32,
+}
+
+/// A struct with map fields
+#[derive(Dox, Serialize)]
+struct MapStruct {
+    /// Listeners by name
+    listeners: HashMap<String, Listener>,
+    /// Limits by name
+    limits: BTreeMap<String, u32>,
+    /// Levels by module
+    levels: IndexMap<String, Level>,
+}
+
+/// Options shared between configs
+#[derive(Dox, Serialize)]
+struct CommonOptions {
+    /// Request timeout in seconds
+    timeout: u32,
+}
+
+/// A struct that flattens shared options
+#[derive(Dox, Serialize)]
+struct FlattenStruct {
+    /// The service name
+    name: String,
+    /// Shared options
+    #[serde(flatten)]
+    common: CommonOptions,
+}
+
+/// How a job runs
+#[derive(Dox, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RunMode {
+    /// On this machine
+    Local { path: String },
+    /// On another machine
+    Remote { url: String },
+}
+
+/// A job whose mode and labels sit alongside its name
+#[derive(Dox, Serialize, Deserialize)]
+struct FlattenKeys {
+    /// The job name
+    name: String,
+    /// How to run
+    #[serde(flatten)]
+    mode: RunMode,
+    /// Labels to attach
+    #[serde(flatten)]
+    labels: HashMap<String, String>,
+}
+
+fn default_port() -> u16 {
+    8080
+}
+
+/// A type that can't be serialized
+#[derive(Dox, Default, Deserialize)]
+struct Opaque {
+    /// A value
+    value: u32,
+}
+
+/// A struct with defaults
+#[derive(Dox, Serialize, Deserialize)]
+struct DefaultStruct {
+    /// Uses the type's default
+    #[serde(default)]
+    name: String,
+    /// Uses a function
+    #[serde(default = "default_port")]
+    port: u16,
+    /// Has a default that can't be serialized
+    #[serde(default, skip_serializing)]
+    opaque: Opaque,
+    /// Has no default
+    required: bool,
+}
+
+/// A struct with a container-level default
+#[derive(Dox, Serialize, Deserialize)]
+#[serde(default)]
+struct ContainerDefaultStruct {
+    /// Taken from the struct's default
+    retries: u32,
+    /// Overridden by the field
+    #[serde(default = "default_port")]
+    port: u16,
+}
+
+impl Default for ContainerDefaultStruct {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            port: 1,
+        }
+    }
+}
+
+/// A struct documented through dox attributes
+#[derive(Dox, Serialize)]
+#[dox(type_name = "Settings", doc = "Overridden struct docs")]
+struct DoxAttrStruct {
+    /// Renamed by both, dox wins
+    #[serde(rename = "serde_name")]
+    #[dox(rename = "dox_name")]
+    renamed: String,
+    /// Original docs
+    #[dox(doc = "Overridden docs", example = 8080)]
+    port: u16,
+    /// Not documented at all
+    #[dox(skip)]
+    skipped: u32,
+    /// Kept out of human-readable docs
+    #[dox(hidden)]
+    internal: bool,
+}
+
+/// An enum documented through dox attributes
+#[derive(Dox, Serialize)]
+enum DoxAttrEnum {
+    /// Renamed
+    #[dox(rename = "first")]
+    One,
+    /// Not documented at all
+    #[dox(skip)]
+    Two,
+    /// Kept out of human-readable docs
+    #[dox(hidden)]
+    Three,
+}
+
+/// A struct using the same enum twice
+#[derive(Dox, Serialize)]
+struct SharedEnumStruct {
+    /// Level for the console
+    console: Level,
+    /// Level for the log file
+    file: Level,
+}
+
+/// How a failed request is retried
+#[derive(Dox, Serialize)]
+struct RetryPolicy {
+    /// How many times to retry
+    attempts: u32,
+}
+
+/// An upstream server
+#[derive(Dox, Serialize)]
+struct Upstream {
+    /// The server's address
+    address: String,
+    /// Retries for this server
+    retry: Option<RetryPolicy>,
+}
+
+/// A struct that uses the same type in several places
+#[derive(Dox, Serialize)]
+struct RegistryStruct {
+    /// The default retry policy
+    retry: RetryPolicy,
+    /// Servers to forward to
+    upstreams: Vec<Upstream>,
+    /// Retry policies by route
+    routes: HashMap<String, RetryPolicy>,
+}
+
+/// A group of rules
+#[derive(Dox, Serialize)]
+struct RuleGroup {
+    /// The group's name
+    name: String,
+    /// Groups nested within this one
+    groups: Vec<RuleGroup>,
+}
+
+/// A struct whose field is a recursive type
+#[derive(Dox, Serialize)]
+struct RecursiveStruct {
+    /// The top-level rules
+    rules: RuleGroup,
+}
+
+/// Trees, each of which holds more trees
+#[derive(Dox, Serialize)]
+struct Forest(Vec<Tree>);
+
+/// A tree
+#[derive(Dox, Serialize)]
+struct Tree {
+    /// The trees growing from this one
+    children: Forest,
+}
+
+/// A TCP port
+#[derive(Dox, Serialize)]
+struct Port(u16);
+
+#[derive(Dox, Serialize)]
+struct Point(f64, f64);
+
+#[derive(Dox, Serialize)]
+struct Marker;
+
+/// A secret, kept out of logs
+#[derive(Dox, Serialize)]
+#[serde(transparent)]
+struct Secret {
+    value: String,
+}
+
+/// A struct with tuple, newtype and unit struct fields
+#[derive(Dox, Serialize)]
+struct TupleStruct {
+    /// The port to listen on
+    port: Port,
+    /// Where to draw
+    origin: Point,
+    /// A marker
+    marker: Marker,
+    /// The access token
+    token: Secret,
+    backup_port: Port,
+}
+
+/// A user account
+#[derive(Dox, Serialize)]
+struct User {
+    /// The user's login
+    login: String,
+}
+
+/// Settings with defaults that depend on a type parameter
+#[derive(Dox, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct GenericDefaults<T> {
+    /// The item to use
+    item: T,
+    /// The items to try first
+    #[serde(default)]
+    items: Vec<T>,
+    /// How many times to try
+    #[serde(default = "default_retries")]
+    retries: u32,
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+/// One page of results
+#[derive(Dox, Serialize)]
+struct Page<T> {
+    /// The results on this page
+    items: Vec<T>,
+    /// The number of results on all pages
+    total: u32,
+}
+
+/// Search results, in pages of users and of names
+#[derive(Dox, Serialize)]
+struct SearchResults {
+    users: Page<User>,
+    names: Page<String>,
+    client: client::Settings,
+    server: server::Settings,
+}
+
+mod client {
+    use super::*;
+
+    /// Settings for the client
+    #[derive(Dox, Serialize)]
+    pub struct Settings {
+        pub retries: u32,
+    }
+}
+
+mod server {
+    use super::*;
+
+    /// Settings for the server
+    #[derive(Dox, Serialize)]
+    pub struct Settings {
+        pub port: u16,
+    }
+}
+
+/// A value that may be given in one of two ways
+#[derive(Dox, Serialize)]
+#[serde(untagged)]
+enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+/// A struct with fields behind smart pointers and cells
+#[derive(Dox)]
+struct WrapperStruct {
+    /// A boxed struct
+    boxed: Box<InnerStruct>,
+    /// A shared string
+    shared: Arc<String>,
+    /// A borrowed or owned name
+    name: Cow<'static, str>,
+    /// Shared ports
+    ports: Rc<[u16]>,
+    /// A counter
+    counter: Mutex<u32>,
+}
+
+/// A struct with standard library types that have their own formats
+#[derive(Dox, Serialize)]
+struct FormatStruct {
+    /// How long to wait
+    timeout: Duration,
+    /// Where to listen
+    listen: SocketAddr,
+    /// Addresses to allow
+    allow: Vec<IpAddr>,
+    /// How many workers to run
+    workers: NonZeroU32,
+}
+
+/// A struct whose fields are read and written under different names
+#[derive(Dox, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "kebab-case"))]
+struct AliasStruct {
+    /// The text colour
+    #[serde(alias = "colour", alias = "hue")]
+    color: String,
+    /// How many times to retry
+    #[serde(rename(serialize = "retries", deserialize = "max_retries"))]
+    retry_count: u32,
+    /// How long to wait
+    wait_secs: u32,
+}
+
+/// A kind of storage
+#[derive(Dox, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum Storage {
+    /// Keep everything in memory
+    #[serde(alias = "mem", alias = "ram")]
+    Memory,
+    /// Store on disk
+    #[serde(rename = "filesystem")]
+    Disk { path: String },
+    /// Only used in tests
+    #[serde(skip)]
+    Fake,
+    /// Never read from a config
+    #[serde(skip_deserializing)]
+    Migrated,
+    /// Written out, but still accepted
+    #[serde(skip_serializing)]
+    Legacy,
+    /// A storage kind from a newer version
+    #[serde(other)]
+    Unknown,
+}
+
+/// Where to send logs
+#[derive(Dox, Serialize, Deserialize)]
+#[serde(rename_all_fields = "camelCase")]
+enum LogSink {
+    /// A file on disk
+    File { file_path: String },
+    /// A remote collector
+    #[serde(rename_all = "kebab-case")]
+    Remote { server_url: String },
+}
+
+/// A config section that rejects keys it doesn't know
+#[derive(Dox, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StrictStruct {
+    /// The listen address
+    address: String,
+}
+
+/// A section, tagged with its kind
+#[derive(Dox, Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum TaggedSection {
+    Listen(StrictStruct),
+}
+
+/// A job whose state is saved alongside its config
+#[derive(Dox, Serialize, Deserialize)]
+struct Job {
+    /// The command to run
+    command: String,
+    /// The password to run it with
+    #[serde(skip_serializing)]
+    password: String,
+    /// When the job last ran
+    #[serde(skip_deserializing, rename(serialize = "lastRun"))]
+    last_run: u64,
+    /// Extra arguments
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    args: Vec<String>,
+    /// Scratch state
+    #[serde(skip)]
+    scratch: u32,
+}
+
 /// This is a struct with snake_case rename
 #[derive(Dox, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -75,6 +526,58 @@ enum ScreamingSnakeCaseEnum {
     VariantThree,
 }
 
+/// An enum with data-carrying variants
+#[derive(Dox, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum PayloadEnum {
+    /// A unit variant
+    Plain,
+    /// A tuple variant
+    Compound(Vec<String>, u32),
+    /// A struct variant
+    #[serde(rename_all = "camelCase")]
+    Listen {
+        /// The host to bind
+        host_name: String,
+        /// The socket to bind
+        listener: Listener,
+    },
+}
+
+/// An internally tagged enum
+#[derive(Dox, Serialize)]
+#[serde(tag = "type")]
+enum InternalEnum {
+    /// Listen on a socket
+    Listen(Listener),
+}
+
+/// An adjacently tagged enum
+#[derive(Dox, Serialize)]
+#[serde(tag = "t", content = "c")]
+enum AdjacentEnum {
+    /// A count
+    Count(u32),
+}
+
+/// An untagged enum
+#[derive(Dox, Serialize)]
+#[serde(untagged)]
+enum UntaggedEnum {
+    /// A count
+    Count(u32),
+    /// A name
+    Name(String),
+}
+
+/// A number, whole or not
+#[derive(Dox, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Number {
+    Int(u32),
+    Float(f64),
+}
+
 /// This is a struct with a skipped field
 #[derive(Dox, Serialize)]
 struct SkippedFieldStruct {
@@ -88,53 +591,928 @@ struct SkippedFieldStruct {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use dox::{Container, Enum, Field, Primitive, Typ, Variant};
+    use dox::{
+        Container, DefaultKind, DefaultValue, Direction, Enum, Field, FieldMeta, Primitive,
+        Representation, Schema, Typ, Variant, VariantFields,
+    };
     use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    /// Whether the JSON Schema rendered for `T` accepts `value`.
+    fn schema_accepts<T: Dox>(value: &serde_json::Value) -> bool {
+        let schema = dox::render::to_json_schema(&T::dox());
+        jsonschema::validator_for(&schema)
+            .expect("a valid schema")
+            .is_valid(value)
+    }
 
     #[test]
     fn test_dox() {
         let expected = Field::Container(Container {
-            name: "TestStruct".to_string(),
+            meta: FieldMeta {
+                name: "TestStruct".to_string(),
+                doc: "This is a test struct".to_string(),
+                ..FieldMeta::default()
+            },
             type_name: "TestStruct".to_string(),
+            path: "tests::TestStruct".to_string(),
+            type_doc: "This is a test struct".to_string(),
             fields: vec![
                 Field::Primitive(Primitive {
-                    name: "test".to_string(),
+                    meta: FieldMeta {
+                        name: "test".to_string(),
+                        doc: "This is a test field".to_string(),
+                        ..FieldMeta::default()
+                    },
                     typ: Typ::String,
-                    doc: "This is a test field".to_string(),
+                    type_name: None,
                 }),
                 Field::Container(Container {
-                    name: "nested".to_string(),
+                    meta: FieldMeta {
+                        name: "nested".to_string(),
+                        doc: "This is a nested struct".to_string(),
+                        ..FieldMeta::default()
+                    },
                     type_name: "InnerStruct".to_string(),
+                    path: "tests::InnerStruct".to_string(),
+                    type_doc: String::new(),
                     fields: vec![Field::Primitive(Primitive {
-                        name: "inner".to_string(),
+                        meta: FieldMeta {
+                            name: "inner".to_string(),
+                            doc: "This is an inner field".to_string(),
+                            ..FieldMeta::default()
+                        },
                         typ: Typ::I32,
-                        doc: "This is an inner field".to_string(),
+                        type_name: None,
                     })],
-                    doc: "This is a nested struct".to_string(),
+                    deny_unknown_fields: false,
                 }),
                 Field::Primitive(Primitive {
-                    name: "vector".to_string(),
-                    typ: Typ::Vec(Box::new(Typ::I32)),
-                    doc: "This is a vector of integers".to_string(),
+                    meta: FieldMeta {
+                        name: "vector".to_string(),
+                        doc: "This is a vector of integers".to_string(),
+                        ..FieldMeta::default()
+                    },
+                    typ: Typ::Vec(Box::new(i32::dox())),
+                    type_name: None,
                 }),
             ],
-            doc: "This is a test struct".to_string(),
+            deny_unknown_fields: false,
         });
 
         assert_eq!(TestStruct::dox(), expected);
     }
 
+    #[test]
+    fn test_vec_of_structs_and_enums() {
+        let expected = Field::Container(Container {
+            meta: FieldMeta {
+                name: "VecStruct".to_string(),
+                doc: "A struct holding vectors of structs and enums".to_string(),
+                ..FieldMeta::default()
+            },
+            type_name: "VecStruct".to_string(),
+            path: "tests::VecStruct".to_string(),
+            type_doc: "A struct holding vectors of structs and enums".to_string(),
+            fields: vec![
+                Field::Primitive(Primitive {
+                    meta: FieldMeta {
+                        name: "listeners".to_string(),
+                        doc: "Sockets to listen on".to_string(),
+                        ..FieldMeta::default()
+                    },
+                    typ: Typ::Vec(Box::new(Field::Container(Container {
+                        meta: FieldMeta {
+                            name: "Listener".to_string(),
+                            doc: "A listening socket".to_string(),
+                            ..FieldMeta::default()
+                        },
+                        type_name: "Listener".to_string(),
+                        path: "tests::Listener".to_string(),
+                        type_doc: "A listening socket".to_string(),
+                        fields: vec![Field::Primitive(Primitive {
+                            meta: FieldMeta {
+                                name: "port".to_string(),
+                                doc: "The port to bind".to_string(),
+                                ..FieldMeta::default()
+                            },
+                            typ: Typ::U16,
+                            type_name: None,
+                        })],
+                        deny_unknown_fields: false,
+                    }))),
+                    type_name: None,
+                }),
+                Field::Primitive(Primitive {
+                    meta: FieldMeta {
+                        name: "levels".to_string(),
+                        doc: "Enabled levels".to_string(),
+                        ..FieldMeta::default()
+                    },
+                    typ: Typ::Vec(Box::new(Field::Enum(Enum {
+                        meta: FieldMeta {
+                            name: "Level".to_string(),
+                            doc: "Log verbosity".to_string(),
+                            ..FieldMeta::default()
+                        },
+                        type_name: "Level".to_string(),
+                        path: "tests::Level".to_string(),
+                        type_doc: "Log verbosity".to_string(),
+                        variants: vec![
+                            Variant {
+                                name: "Error".to_string(),
+                                doc: "Only errors".to_string(),
+                                fields: VariantFields::Unit,
+                                hidden: false,
+                                aliases: Vec::new(),
+                                serialized_name: None,
+                                other: false,
+                            },
+                            Variant {
+                                name: "Debug".to_string(),
+                                doc: "Everything".to_string(),
+                                fields: VariantFields::Unit,
+                                hidden: false,
+                                aliases: Vec::new(),
+                                serialized_name: None,
+                                other: false,
+                            },
+                        ],
+                        representation: Representation::External,
+                    }))),
+                    type_name: None,
+                }),
+            ],
+            deny_unknown_fields: false,
+        });
+
+        assert_eq!(VecStruct::dox(), expected);
+    }
+
+    #[test]
+    fn test_option() {
+        let expected = Field::Container(Container {
+            meta: FieldMeta {
+                name: "OptionStruct".to_string(),
+                doc: "A struct with optional fields".to_string(),
+                ..FieldMeta::default()
+            },
+            type_name: "OptionStruct".to_string(),
+            path: "tests::OptionStruct".to_string(),
+            type_doc: "A struct with optional fields".to_string(),
+            fields: vec![
+                Field::Primitive(Primitive {
+                    meta: FieldMeta {
+                        name: "name".to_string(),
+                        doc: "An optional string".to_string(),
+                        optional: true,
+                        ..FieldMeta::default()
+                    },
+                    typ: Typ::String,
+                    type_name: None,
+                }),
+                Field::Container(Container {
+                    meta: FieldMeta {
+                        name: "listener".to_string(),
+                        doc: "An optional nested struct".to_string(),
+                        optional: true,
+                        ..FieldMeta::default()
+                    },
+                    type_name: "Listener".to_string(),
+                    path: "tests::Listener".to_string(),
+                    type_doc: "A listening socket".to_string(),
+                    fields: vec![Field::Primitive(Primitive {
+                        meta: FieldMeta {
+                            name: "port".to_string(),
+                            doc: "The port to bind".to_string(),
+                            ..FieldMeta::default()
+                        },
+                        typ: Typ::U16,
+                        type_name: None,
+                    })],
+                    deny_unknown_fields: false,
+                }),
+                Field::Primitive(Primitive {
+                    meta: FieldMeta {
+                        name: "count".to_string(),
+                        doc: "A required integer".to_string(),
+                        ..FieldMeta::default()
+                    },
+                    typ: Typ::U32,
+                    type_name: None,
+                }),
+            ],
+            deny_unknown_fields: false,
+        });
+
+        assert_eq!(OptionStruct::dox(), expected);
+
+        // serde writes a `None` as null.
+        let value = serde_json::to_value(OptionStruct {
+            name: None,
+            listener: None,
+            count: 1,
+        })
+        .unwrap();
+        assert!(schema_accepts::<OptionStruct>(&value));
+        assert!(schema_accepts::<OptionStruct>(&json!({ "count": 1 })));
+        assert!(!schema_accepts::<OptionStruct>(&json!({ "count": null })));
+    }
+
+    #[test]
+    fn test_maps() {
+        let map = |key: Field, value: Field| Typ::Map(Box::new(key), Box::new(value));
+        let expected = Field::Container(Container {
+            meta: FieldMeta {
+                name: "MapStruct".to_string(),
+                doc: "A struct with map fields".to_string(),
+                ..FieldMeta::default()
+            },
+            type_name: "MapStruct".to_string(),
+            path: "tests::MapStruct".to_string(),
+            type_doc: "A struct with map fields".to_string(),
+            fields: vec![
+                Field::Primitive(Primitive {
+                    meta: FieldMeta {
+                        name: "listeners".to_string(),
+                        doc: "Listeners by name".to_string(),
+                        ..FieldMeta::default()
+                    },
+                    typ: map(String::dox(), Listener::dox()),
+                    type_name: None,
+                }),
+                Field::Primitive(Primitive {
+                    meta: FieldMeta {
+                        name: "limits".to_string(),
+                        doc: "Limits by name".to_string(),
+                        ..FieldMeta::default()
+                    },
+                    typ: map(String::dox(), u32::dox()),
+                    type_name: None,
+                }),
+                Field::Primitive(Primitive {
+                    meta: FieldMeta {
+                        name: "levels".to_string(),
+                        doc: "Levels by module".to_string(),
+                        ..FieldMeta::default()
+                    },
+                    typ: map(String::dox(), Level::dox()),
+                    type_name: None,
+                }),
+            ],
+            deny_unknown_fields: false,
+        });
+
+        assert_eq!(MapStruct::dox(), expected);
+    }
+
+    #[test]
+    fn test_flatten() {
+        let expected = Field::Container(Container {
+            meta: FieldMeta {
+                name: "FlattenStruct".to_string(),
+                doc: "A struct that flattens shared options".to_string(),
+                ..FieldMeta::default()
+            },
+            type_name: "FlattenStruct".to_string(),
+            path: "tests::FlattenStruct".to_string(),
+            type_doc: "A struct that flattens shared options".to_string(),
+            fields: vec![
+                Field::Primitive(Primitive {
+                    meta: FieldMeta {
+                        name: "name".to_string(),
+                        doc: "The service name".to_string(),
+                        ..FieldMeta::default()
+                    },
+                    typ: Typ::String,
+                    type_name: None,
+                }),
+                Field::Primitive(Primitive {
+                    meta: FieldMeta {
+                        name: "timeout".to_string(),
+                        doc: "Request timeout in seconds".to_string(),
+                        flattened_from: Some("CommonOptions".to_string()),
+                        ..FieldMeta::default()
+                    },
+                    typ: Typ::U32,
+                    type_name: None,
+                }),
+            ],
+            deny_unknown_fields: false,
+        });
+
+        assert_eq!(FlattenStruct::dox(), expected);
+
+        // The flattened struct has no section, so its name is not a link.
+        let markdown = dox::render::<FlattenStruct, _>(&dox::render::Markdown);
+        assert!(markdown.contains("Request timeout in seconds<br>From `CommonOptions` |"));
+        assert!(!markdown.contains("## CommonOptions"));
+    }
+
+    #[test]
+    fn test_flatten_keys() {
+        let Field::Container(container) = FlattenKeys::dox() else {
+            panic!("expected a container");
+        };
+        let flattened: Vec<_> = container.fields.iter().map(Field::is_flattened).collect();
+        assert_eq!(flattened, vec![false, true, true]);
+
+        let text = dox::render::<FlattenKeys, _>(&dox::render::Text);
+        assert!(text.contains("- the keys of a RunMode variant (RunMode): How to run\n"));
+        assert!(text.contains("- any other key (String): Labels to attach\n"));
+        let markdown = dox::render::<FlattenKeys, _>(&dox::render::Markdown);
+        assert!(markdown.contains("| *any other key* | `String` | no | Labels to attach |"));
+        let toml = dox::render::<FlattenKeys, _>(&dox::render::TomlExample);
+        assert!(toml.contains("# Flattened: any other key\n# name = \"\"\n"));
+
+        let schema = dox::render::to_json_schema(&FlattenKeys::dox());
+        assert!(schema["properties"].get("mode").is_none());
+        assert!(schema["properties"].get("labels").is_none());
+        assert_eq!(schema["required"], json!(["name"]));
+        let value = json!({ "name": "build", "local": { "path": "/src" }, "team": "ci" });
+        assert!(serde_json::from_value::<FlattenKeys>(value.clone()).is_ok());
+        assert!(schema_accepts::<FlattenKeys>(&value));
+        let bad_label = json!({ "name": "build", "local": { "path": "/src" }, "team": 1 });
+        assert!(!schema_accepts::<FlattenKeys>(&bad_label));
+        let no_mode = json!({ "name": "build", "team": "ci" });
+        assert!(serde_json::from_value::<FlattenKeys>(no_mode.clone()).is_err());
+        assert!(!schema_accepts::<FlattenKeys>(&no_mode));
+    }
+
+    #[test]
+    fn test_defaults() {
+        let defaults = |field: Field| match field {
+            Field::Container(c) => c
+                .fields
+                .iter()
+                .map(|f| f.default().cloned())
+                .collect::<Vec<_>>(),
+            _ => panic!("expected a container"),
+        };
+        assert_eq!(
+            defaults(DefaultStruct::dox()),
+            vec![
+                Some(DefaultValue {
+                    kind: DefaultKind::Trait,
+                    value: Some("".into()),
+                }),
+                Some(DefaultValue {
+                    kind: DefaultKind::Path("default_port".to_string()),
+                    value: Some(8080.into()),
+                }),
+                Some(DefaultValue {
+                    kind: DefaultKind::Trait,
+                    value: None,
+                }),
+                None,
+            ]
+        );
+        assert_eq!(
+            defaults(ContainerDefaultStruct::dox()),
+            vec![
+                Some(DefaultValue {
+                    kind: DefaultKind::Trait,
+                    value: Some(3.into()),
+                }),
+                Some(DefaultValue {
+                    kind: DefaultKind::Path("default_port".to_string()),
+                    value: Some(8080.into()),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dox_attrs() {
+        let Field::Container(container) = DoxAttrStruct::dox() else {
+            panic!("expected a container");
+        };
+        assert_eq!(container.type_name, "Settings");
+        assert_eq!(container.meta.doc, "Overridden struct docs");
+        let fields: Vec<_> = container
+            .fields
+            .iter()
+            .map(|f| (f.name(), f.doc(), f.is_hidden(), f.example().cloned()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("dox_name", "Renamed by both, dox wins", false, None),
+                ("port", "Overridden docs", false, Some(8080.into())),
+                ("internal", "Kept out of human-readable docs", true, None),
+            ]
+        );
+
+        let Field::Enum(enum_type) = DoxAttrEnum::dox() else {
+            panic!("expected an enum");
+        };
+        let variants: Vec<_> = enum_type
+            .variants
+            .iter()
+            .map(|v| (v.name.as_str(), v.hidden))
+            .collect();
+        assert_eq!(variants, vec![("first", false), ("Three", true)]);
+    }
+
+    #[test]
+    fn test_enum_type_name() {
+        let Field::Container(container) = SharedEnumStruct::dox() else {
+            panic!("expected a container");
+        };
+        let names: Vec<_> = container
+            .fields
+            .iter()
+            .map(|f| (f.name().to_string(), f.type_name()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("console".to_string(), "Level".to_string()),
+                ("file".to_string(), "Level".to_string()),
+            ]
+        );
+
+        let rendered = dox::render::<SharedEnumStruct, _>(&dox::render::Text);
+        assert_eq!(rendered.matches("Level (enum)").count(), 1);
+        assert!(rendered.contains("- console (Level): Level for the console"));
+    }
+
+    #[test]
+    fn test_schema() {
+        let schema = Schema::of::<RegistryStruct>();
+        let paths: Vec<_> = schema
+            .definitions
+            .iter()
+            .map(|d| match d {
+                Field::Container(c) => c.path.as_str(),
+                _ => panic!("expected a container"),
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "tests::RegistryStruct",
+                "tests::RetryPolicy",
+                "tests::Upstream",
+            ]
+        );
+        let Field::Container(root) = &schema.root else {
+            panic!("expected a container");
+        };
+        let Field::Primitive(retry) = &root.fields[0] else {
+            panic!("expected a reference");
+        };
+        assert_eq!(
+            retry.typ,
+            Typ::Ref {
+                path: "tests::RetryPolicy".to_string(),
+                type_name: "RetryPolicy".to_string(),
+            }
+        );
+        assert_eq!(schema.expand(&schema.root), RegistryStruct::dox());
+
+        let rendered = dox::render::<RegistryStruct, _>(&dox::render::Text);
+        assert_eq!(rendered.matches("RetryPolicy\n===").count(), 1);
+        assert!(rendered.contains("- retry (RetryPolicy, optional): Retries for this server"));
+        assert!(
+            rendered.contains("- routes (map of String → RetryPolicy): Retry policies by route")
+        );
+        // The section documents the type, whichever field uses it first.
+        assert!(rendered.contains("RetryPolicy\n===========\nHow a failed request is retried\n"));
+
+        let json = dox::render::to_json_schema(&RegistryStruct::dox());
+        assert_eq!(
+            json["$defs"]["RetryPolicy"]["description"],
+            "How a failed request is retried"
+        );
+        assert_eq!(
+            json["properties"]["retry"]["description"],
+            "The default retry policy"
+        );
+    }
+
+    #[test]
+    fn test_recursive() {
+        let Field::Container(group) = RuleGroup::dox() else {
+            panic!("expected a container");
+        };
+        assert_eq!(
+            group.fields[1],
+            Field::Primitive(Primitive {
+                meta: FieldMeta {
+                    name: "groups".to_string(),
+                    doc: "Groups nested within this one".to_string(),
+                    ..FieldMeta::default()
+                },
+                typ: Typ::Vec(Box::new(Field::Primitive(Primitive::new(Typ::Ref {
+                    path: "tests::RuleGroup".to_string(),
+                    type_name: "RuleGroup".to_string(),
+                })))),
+                type_name: None,
+            })
+        );
+
+        let text = dox::render::<RecursiveStruct, _>(&dox::render::Text);
+        assert_eq!(text.matches("RuleGroup\n===").count(), 1);
+        assert!(text
+            .contains("- groups (Vec<RuleGroup>, see RuleGroup): Groups nested within this one"));
+
+        let toml = dox::render::<RecursiveStruct, _>(&dox::render::TomlExample);
+        assert!(toml.contains("# See RuleGroup\ngroups = []\n"));
+
+        let schema = dox::render::to_json_schema(&RecursiveStruct::dox());
+        assert_eq!(
+            schema["$defs"]["RuleGroup"]["properties"]["groups"]["items"]["$ref"],
+            "#/$defs/RuleGroup"
+        );
+    }
+
+    #[test]
+    fn test_recursive_through_newtype() {
+        let Field::Container(tree) = Tree::dox() else {
+            panic!("expected a container");
+        };
+        let Field::Primitive(forest) = &tree.fields[0] else {
+            panic!("expected a newtype");
+        };
+        assert_eq!(forest.type_name.as_deref(), Some("Forest"));
+    }
+
+    #[test]
+    #[should_panic(expected = "`Forest` contains itself")]
+    fn test_recursive_newtype() {
+        Forest::dox();
+    }
+
+    #[test]
+    fn test_tuple_structs() {
+        let primitive = |name: &str, typ: Typ, doc: &str| {
+            let mut field = Field::Primitive(Primitive::new(typ));
+            field.set_name(name);
+            field.set_doc(doc);
+            field
+        };
+        let newtype = |name: &str, type_name: &str, typ: Typ, doc: &str| {
+            let mut field = primitive(name, typ, doc);
+            if let Field::Primitive(prim) = &mut field {
+                prim.type_name = Some(type_name.to_string());
+            }
+            field
+        };
+        assert_eq!(Port::dox(), newtype("Port", "Port", Typ::U16, "A TCP port"));
+        assert_eq!(
+            Secret::dox(),
+            newtype(
+                "Secret",
+                "Secret",
+                Typ::String,
+                "A secret, kept out of logs"
+            )
+        );
+
+        let Field::Container(container) = TupleStruct::dox() else {
+            panic!("expected a container");
+        };
+        assert_eq!(
+            container.fields,
+            vec![
+                newtype("port", "Port", Typ::U16, "The port to listen on"),
+                primitive(
+                    "origin",
+                    Typ::Tuple(vec![
+                        primitive("", Typ::F64, ""),
+                        primitive("", Typ::F64, ""),
+                    ]),
+                    "Where to draw",
+                ),
+                primitive("marker", Typ::Unit, "A marker"),
+                newtype("token", "Secret", Typ::String, "The access token"),
+                newtype("backup_port", "Port", Typ::U16, "A TCP port"),
+            ]
+        );
+
+        let rendered = dox::render::<TupleStruct, _>(&dox::render::Text);
+        assert!(rendered.contains("- port (Port): The port to listen on"));
+        assert!(rendered.contains("- origin ((f64, f64)): Where to draw"));
+        assert!(rendered.contains("- marker (()): A marker"));
+        assert!(rendered.contains("- backup_port (Port): A TCP port"));
+    }
+
+    #[test]
+    fn test_generics() {
+        let page = <Page<User>>::dox();
+        assert_eq!(page.type_name(), "Page<User>");
+        let Field::Container(container) = page else {
+            panic!("expected a container");
+        };
+        assert_eq!(container.fields[0].type_name(), "Vec<User>");
+        let Field::Container(strings) = <Page<String>>::dox() else {
+            panic!("expected a container");
+        };
+        assert_eq!(strings.type_name, "Page<String>");
+        assert_ne!(strings.path, container.path);
+        assert_eq!(
+            <Either<u16, String>>::dox().type_name(),
+            "Either<u16, String>"
+        );
+
+        let rendered = dox::render::<Page<User>, _>(&dox::render::Text);
+        assert!(rendered.starts_with("Page<User>\n==========\nOne page of results"));
+        assert!(rendered.contains("User\n====\n"));
+    }
+
+    #[test]
+    fn test_generic_defaults() {
+        let Field::Container(container) = <GenericDefaults<u32>>::dox() else {
+            panic!("expected a container");
+        };
+        let defaults: Vec<_> = container
+            .fields
+            .iter()
+            .map(|f| f.default().cloned())
+            .collect();
+        // Whether `T` has a default, or can be serialized, is not known in the
+        // generic impl, so only the function's default is.
+        assert_eq!(
+            defaults,
+            vec![
+                Some(DefaultValue {
+                    kind: DefaultKind::Trait,
+                    value: None,
+                }),
+                Some(DefaultValue {
+                    kind: DefaultKind::Trait,
+                    value: None,
+                }),
+                Some(DefaultValue {
+                    kind: DefaultKind::Path("default_retries".to_string()),
+                    value: Some(json!(3)),
+                }),
+            ]
+        );
+
+        let toml = dox::render::<GenericDefaults<u32>, _>(&dox::render::TomlExample);
+        assert!(toml.contains("# item = 0\n"));
+        assert!(toml.contains("# items = [0]\n"));
+        assert!(toml.contains("\nretries = 3\n"));
+
+        // So are the tables of a struct parameter.
+        let toml = dox::render::<GenericDefaults<Listener>, _>(&dox::render::TomlExample);
+        assert!(toml.contains("# The item to use\n# [item]\n# # The port to bind\n# port = 0\n"));
+        assert!(toml.contains("# The items to try first\n# [[items]]\n"));
+    }
+
+    #[test]
+    fn test_schema_defs() {
+        let schema = dox::render::to_json_schema(&SearchResults::dox());
+        let names: Vec<_> = schema["$defs"].as_object().unwrap().keys().collect();
+        assert_eq!(
+            names,
+            vec![
+                "Page_String",
+                "Page_User",
+                "Settings",
+                "User",
+                "tests.server.Settings"
+            ]
+        );
+        let value = json!({
+            "users": { "items": [{ "login": "ann" }], "total": 1 },
+            "names": { "items": ["ann"], "total": 1 },
+            "client": { "retries": 3 },
+            "server": { "port": 8080 },
+        });
+        assert!(schema_accepts::<SearchResults>(&value));
+        let swapped = json!({
+            "users": { "items": ["ann"], "total": 1 },
+            "names": { "items": ["ann"], "total": 1 },
+            "client": { "retries": 3 },
+            "server": { "port": 8080 },
+        });
+        assert!(!schema_accepts::<SearchResults>(&swapped));
+
+        // Each `Settings` has a section and anchor of its own.
+        let markdown = dox::render::<SearchResults, _>(&dox::render::Markdown);
+        assert!(markdown.contains("| `client` | [`Settings`](#settings) |"));
+        assert!(markdown.contains("## Settings\n\nSettings for the client"));
+        assert!(
+            markdown.contains("| `server` | [`tests::server::Settings`](#testsserversettings) |")
+        );
+        assert!(markdown.contains("## tests::server::Settings\n\nSettings for the server"));
+    }
+
+    #[test]
+    fn test_wrappers() {
+        let Field::Container(container) = WrapperStruct::dox() else {
+            panic!("expected a container");
+        };
+        let types: Vec<_> = container.fields.iter().map(Field::type_name).collect();
+        assert_eq!(
+            types,
+            vec!["InnerStruct", "String", "String", "Vec<u16>", "u32"]
+        );
+        let Field::Container(boxed) = &container.fields[0] else {
+            panic!("expected a container");
+        };
+        let Field::Container(inner) = InnerStruct::dox() else {
+            panic!("expected a container");
+        };
+        assert_eq!(boxed.fields, inner.fields);
+    }
+
+    #[test]
+    fn test_formats() {
+        let rendered = dox::render::<FormatStruct, _>(&dox::render::Text);
+        assert!(rendered.contains(
+            "- timeout (Duration, written as an object with `secs` and `nanos`): How long to wait"
+        ));
+        assert!(rendered.contains(
+            "- listen (SocketAddr, written as an IP address and port, e.g. \"127.0.0.1:8080\"): \
+             Where to listen"
+        ));
+        assert!(rendered.contains("- allow (Vec<IpAddr>): Addresses to allow"));
+        assert!(rendered.contains(
+            "- workers (NonZero<u32>, written as an integer other than 0): How many workers to run"
+        ));
+
+        let schema = dox::render::to_json_schema(&FormatStruct::dox());
+        assert_eq!(schema["properties"]["listen"]["format"], "socket-addr");
+        assert_eq!(schema["properties"]["workers"]["not"]["const"], 0);
+    }
+
+    #[test]
+    fn test_aliases() {
+        let Field::Container(container) = AliasStruct::dox() else {
+            panic!("expected a container");
+        };
+        let names: Vec<_> = container
+            .fields
+            .iter()
+            .map(|f| (f.name(), f.serialized_name(), f.aliases().to_vec()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("color", None, vec!["colour".to_string(), "hue".to_string()]),
+                ("max_retries", Some("retries"), vec![]),
+                ("wait-secs", Some("waitSecs"), vec![]),
+            ]
+        );
+
+        let rendered = dox::render::<AliasStruct, _>(&dox::render::Text);
+        assert!(
+            rendered.contains("- color (String, also accepted: `colour`, `hue`): The text colour")
+        );
+        assert!(rendered.contains("- max_retries (u32, serialized as `retries`): How many times"));
+
+        let markdown = dox::render::<AliasStruct, _>(&dox::render::Markdown);
+        assert!(markdown.contains(
+            "| `max_retries` | `u32` | yes | How many times to retry<br>Serialized as `retries` |"
+        ));
+
+        let schema = dox::render::to_json_schema(&AliasStruct::dox());
+        assert_eq!(schema["properties"]["hue"]["type"], "string");
+        assert_eq!(schema["required"][0], "max_retries");
+        assert_eq!(schema["allOf"][0]["anyOf"][1]["required"][0], "colour");
+    }
+
+    #[test]
+    fn test_variant_attrs() {
+        let Field::Enum(enum_type) = Storage::dox() else {
+            panic!("expected an enum");
+        };
+        let variants: Vec<_> = enum_type
+            .variants
+            .iter()
+            .map(|v| (v.name.as_str(), v.aliases.clone(), v.other))
+            .collect();
+        assert_eq!(
+            variants,
+            vec![
+                ("memory", vec!["mem".to_string(), "ram".to_string()], false),
+                ("filesystem", vec![], false),
+                ("legacy", vec![], false),
+                ("unknown", vec![], true),
+            ]
+        );
+
+        let rendered = dox::render::<Storage, _>(&dox::render::Text);
+        assert!(
+            rendered.contains("- memory (also accepted: `mem`, `ram`): Keep everything in memory")
+        );
+        assert!(rendered.contains("Any other value maps to unknown."));
+
+        let schema = dox::render::to_json_schema(&Storage::dox());
+        assert_eq!(schema["oneOf"][0]["properties"]["kind"]["enum"][2], "ram");
+        for value in [
+            json!({ "kind": "memory" }),
+            json!({ "kind": "ram" }),
+            json!({ "kind": "filesystem", "path": "/var/lib" }),
+            json!({ "kind": "legacy" }),
+            json!({ "kind": "cloud" }),
+        ] {
+            assert!(serde_json::from_value::<Storage>(value.clone()).is_ok());
+            assert!(schema_accepts::<Storage>(&value), "{}", value);
+        }
+        let missing_path = json!({ "kind": "filesystem" });
+        assert!(serde_json::from_value::<Storage>(missing_path.clone()).is_err());
+        assert!(!schema_accepts::<Storage>(&missing_path));
+    }
+
+    #[test]
+    fn test_rename_all_fields() {
+        let Field::Enum(enum_type) = LogSink::dox() else {
+            panic!("expected an enum");
+        };
+        let names: Vec<_> = enum_type
+            .variants
+            .iter()
+            .flat_map(|v| v.fields.fields().iter().map(Field::name))
+            .collect();
+        assert_eq!(names, vec!["filePath", "server-url"]);
+    }
+
+    #[test]
+    fn test_deny_unknown_fields() {
+        let Field::Container(container) = StrictStruct::dox() else {
+            panic!("expected a container");
+        };
+        assert!(container.deny_unknown_fields);
+
+        let rendered = dox::render::<StrictStruct, _>(&dox::render::Text);
+        assert!(rendered.contains("Unknown keys are rejected."));
+
+        let schema = dox::render::to_json_schema(&StrictStruct::dox());
+        assert_eq!(schema["additionalProperties"], false);
+        let schema = dox::render::to_json_schema(&Job::dox());
+        assert!(schema.get("additionalProperties").is_none());
+
+        let listen = json!({ "type": "Listen", "address": "[::]:80" });
+        assert!(serde_json::from_value::<TaggedSection>(listen.clone()).is_ok());
+        assert!(schema_accepts::<TaggedSection>(&listen));
+        let unknown = json!({ "type": "Listen", "address": "[::]:80", "port": 80 });
+        assert!(serde_json::from_value::<TaggedSection>(unknown.clone()).is_err());
+        assert!(!schema_accepts::<TaggedSection>(&unknown));
+    }
+
+    #[test]
+    fn test_directions() {
+        let Field::Container(container) = Job::dox() else {
+            panic!("expected a container");
+        };
+        let directions: Vec<_> = container
+            .fields
+            .iter()
+            .map(|f| (f.name(), f.direction()))
+            .collect();
+        assert_eq!(
+            directions,
+            vec![
+                ("command", Direction::Both),
+                ("password", Direction::Input),
+                ("last_run", Direction::Output),
+                ("args", Direction::Both),
+            ]
+        );
+
+        let rendered = dox::render::<Job, _>(&dox::render::Text);
+        assert!(rendered.contains("- password (String): The password to run it with"));
+        // A field that is only written is documented under the name written.
+        assert!(rendered.contains("- lastRun (u64, output only): When the job last ran"));
+
+        let example = dox::render::<Job, _>(&dox::render::TomlExample);
+        assert!(example.contains("password = "));
+        assert!(!example.contains("last_run") && !example.contains("lastRun"));
+
+        let schema = dox::render::to_json_schema(&Job::dox());
+        assert_eq!(schema["properties"]["password"]["writeOnly"], true);
+        assert_eq!(schema["properties"]["lastRun"]["readOnly"], true);
+        assert_eq!(schema["required"][0], "command");
+        assert_eq!(schema["required"][1], "password");
+        assert_eq!(schema["required"].as_array().unwrap().len(), 2);
+    }
+
     #[test]
     fn test_snake_case_rename() {
         let expected = Field::Container(Container {
-            name: "SnakeCaseStruct".to_string(),
+            meta: FieldMeta {
+                name: "SnakeCaseStruct".to_string(),
+                doc: "This is a struct with snake_case rename".to_string(),
+                ..FieldMeta::default()
+            },
             type_name: "SnakeCaseStruct".to_string(),
+            path: "tests::SnakeCaseStruct".to_string(),
+            type_doc: "This is a struct with snake_case rename".to_string(),
             fields: vec![Field::Primitive(Primitive {
-                name: "camel_case_field".to_string(),
+                meta: FieldMeta {
+                    name: "camel_case_field".to_string(),
+                    doc: "This is a camel case field".to_string(),
+                    ..FieldMeta::default()
+                },
                 typ: Typ::String,
-                doc: "This is a camel case field".to_string(),
+                type_name: None,
             })],
-            doc: "This is a struct with snake_case rename".to_string(),
+            deny_unknown_fields: false,
         });
 
         assert_eq!(SnakeCaseStruct::dox(), expected);
@@ -143,14 +1521,24 @@ mod tests {
     #[test]
     fn test_camel_case_rename() {
         let expected = Field::Container(Container {
-            name: "CamelCaseStruct".to_string(),
+            meta: FieldMeta {
+                name: "CamelCaseStruct".to_string(),
+                doc: "This is a struct with camelCase rename".to_string(),
+                ..FieldMeta::default()
+            },
             type_name: "CamelCaseStruct".to_string(),
+            path: "tests::CamelCaseStruct".to_string(),
+            type_doc: "This is a struct with camelCase rename".to_string(),
             fields: vec![Field::Primitive(Primitive {
-                name: "snakeCaseField".to_string(),
+                meta: FieldMeta {
+                    name: "snakeCaseField".to_string(),
+                    doc: "This is a snake case field".to_string(),
+                    ..FieldMeta::default()
+                },
                 typ: Typ::String,
-                doc: "This is a snake case field".to_string(),
+                type_name: None,
             })],
-            doc: "This is a struct with camelCase rename".to_string(),
+            deny_unknown_fields: false,
         });
 
         assert_eq!(CamelCaseStruct::dox(), expected);
@@ -159,22 +1547,44 @@ mod tests {
     #[test]
     fn test_enum() {
         let expected = Field::Enum(Enum {
-            name: "TestEnum".to_string(),
-            doc: "This is a test enum".to_string(),
+            meta: FieldMeta {
+                name: "TestEnum".to_string(),
+                doc: "This is a test enum".to_string(),
+                ..FieldMeta::default()
+            },
+            type_name: "TestEnum".to_string(),
+            path: "tests::TestEnum".to_string(),
+            type_doc: "This is a test enum".to_string(),
             variants: vec![
                 Variant {
                     name: "Variant1".to_string(),
                     doc: "First variant".to_string(),
+                    fields: VariantFields::Unit,
+                    hidden: false,
+                    aliases: Vec::new(),
+                    serialized_name: None,
+                    other: false,
                 },
                 Variant {
                     name: "Variant2".to_string(),
                     doc: "Second variant".to_string(),
+                    fields: VariantFields::Unit,
+                    hidden: false,
+                    aliases: Vec::new(),
+                    serialized_name: None,
+                    other: false,
                 },
                 Variant {
                     name: "Variant3".to_string(),
                     doc: "Third variant".to_string(),
+                    fields: VariantFields::Unit,
+                    hidden: false,
+                    aliases: Vec::new(),
+                    serialized_name: None,
+                    other: false,
                 },
             ],
+            representation: Representation::External,
         });
 
         assert_eq!(TestEnum::dox(), expected);
@@ -183,22 +1593,44 @@ mod tests {
     #[test]
     fn test_snake_case_enum() {
         let expected = Field::Enum(Enum {
-            name: "SnakeCaseEnum".to_string(),
-            doc: "This is a snake_case enum".to_string(),
+            meta: FieldMeta {
+                name: "SnakeCaseEnum".to_string(),
+                doc: "This is a snake_case enum".to_string(),
+                ..FieldMeta::default()
+            },
+            type_name: "SnakeCaseEnum".to_string(),
+            path: "tests::SnakeCaseEnum".to_string(),
+            type_doc: "This is a snake_case enum".to_string(),
             variants: vec![
                 Variant {
                     name: "variant_one".to_string(),
                     doc: "First variant".to_string(),
+                    fields: VariantFields::Unit,
+                    hidden: false,
+                    aliases: Vec::new(),
+                    serialized_name: None,
+                    other: false,
                 },
                 Variant {
                     name: "variant_two".to_string(),
                     doc: "Second variant".to_string(),
+                    fields: VariantFields::Unit,
+                    hidden: false,
+                    aliases: Vec::new(),
+                    serialized_name: None,
+                    other: false,
                 },
                 Variant {
                     name: "variant_three".to_string(),
                     doc: "Third variant".to_string(),
+                    fields: VariantFields::Unit,
+                    hidden: false,
+                    aliases: Vec::new(),
+                    serialized_name: None,
+                    other: false,
                 },
             ],
+            representation: Representation::External,
         });
 
         assert_eq!(SnakeCaseEnum::dox(), expected);
@@ -207,38 +1639,160 @@ mod tests {
     #[test]
     fn test_screaming_snake_case_enum() {
         let expected = Field::Enum(Enum {
-            name: "ScreamingSnakeCaseEnum".to_string(),
-            doc: "This is a SCREAMING_SNAKE_CASE enum".to_string(),
+            meta: FieldMeta {
+                name: "ScreamingSnakeCaseEnum".to_string(),
+                doc: "This is a SCREAMING_SNAKE_CASE enum".to_string(),
+                ..FieldMeta::default()
+            },
+            type_name: "ScreamingSnakeCaseEnum".to_string(),
+            path: "tests::ScreamingSnakeCaseEnum".to_string(),
+            type_doc: "This is a SCREAMING_SNAKE_CASE enum".to_string(),
             variants: vec![
                 Variant {
                     name: "VARIANT_ONE".to_string(),
                     doc: "First variant".to_string(),
+                    fields: VariantFields::Unit,
+                    hidden: false,
+                    aliases: Vec::new(),
+                    serialized_name: None,
+                    other: false,
                 },
                 Variant {
                     name: "VARIANT_TWO".to_string(),
                     doc: "Second variant".to_string(),
+                    fields: VariantFields::Unit,
+                    hidden: false,
+                    aliases: Vec::new(),
+                    serialized_name: None,
+                    other: false,
                 },
                 Variant {
                     name: "VARIANT_THREE".to_string(),
                     doc: "Third variant".to_string(),
+                    fields: VariantFields::Unit,
+                    hidden: false,
+                    aliases: Vec::new(),
+                    serialized_name: None,
+                    other: false,
                 },
             ],
+            representation: Representation::External,
         });
 
         assert_eq!(ScreamingSnakeCaseEnum::dox(), expected);
     }
 
+    #[test]
+    fn test_payload_enum() {
+        let named = |name: &str, doc: &str, mut field: Field| {
+            field.set_name(name);
+            field.set_doc(doc);
+            field
+        };
+        let expected = Field::Enum(Enum {
+            meta: FieldMeta {
+                name: "PayloadEnum".to_string(),
+                doc: "An enum with data-carrying variants".to_string(),
+                ..FieldMeta::default()
+            },
+            type_name: "PayloadEnum".to_string(),
+            path: "tests::PayloadEnum".to_string(),
+            type_doc: "An enum with data-carrying variants".to_string(),
+            variants: vec![
+                Variant {
+                    name: "plain".to_string(),
+                    doc: "A unit variant".to_string(),
+                    fields: VariantFields::Unit,
+                    hidden: false,
+                    aliases: Vec::new(),
+                    serialized_name: None,
+                    other: false,
+                },
+                Variant {
+                    name: "compound".to_string(),
+                    doc: "A tuple variant".to_string(),
+                    fields: VariantFields::Unnamed(vec![Vec::<String>::dox(), u32::dox()]),
+                    hidden: false,
+                    aliases: Vec::new(),
+                    serialized_name: None,
+                    other: false,
+                },
+                Variant {
+                    name: "listen".to_string(),
+                    doc: "A struct variant".to_string(),
+                    fields: VariantFields::Named(vec![
+                        named("hostName", "The host to bind", String::dox()),
+                        named("listener", "The socket to bind", Listener::dox()),
+                    ]),
+                    hidden: false,
+                    aliases: Vec::new(),
+                    serialized_name: None,
+                    other: false,
+                },
+            ],
+            representation: Representation::External,
+        });
+
+        assert_eq!(PayloadEnum::dox(), expected);
+    }
+
+    #[test]
+    fn test_enum_representation() {
+        let representation = |field: Field| match field {
+            Field::Enum(e) => e.representation,
+            _ => panic!("expected an enum"),
+        };
+        assert_eq!(
+            representation(InternalEnum::dox()),
+            Representation::Internal {
+                tag: "type".to_string()
+            }
+        );
+        assert_eq!(
+            representation(AdjacentEnum::dox()),
+            Representation::Adjacent {
+                tag: "t".to_string(),
+                content: "c".to_string()
+            }
+        );
+        assert_eq!(
+            representation(UntaggedEnum::dox()),
+            Representation::Untagged
+        );
+        assert_eq!(representation(TestEnum::dox()), Representation::External);
+    }
+
+    #[test]
+    fn test_untagged_schema() {
+        // Untagged variants may overlap, and the first that matches is used.
+        for value in [json!(1), json!(1.5)] {
+            assert!(serde_json::from_value::<Number>(value.clone()).is_ok());
+            assert!(schema_accepts::<Number>(&value), "{}", value);
+        }
+        assert!(!schema_accepts::<Number>(&json!("one")));
+    }
+
     #[test]
     fn test_skipped_field() {
         let expected = Field::Container(Container {
-            name: "SkippedFieldStruct".to_string(),
+            meta: FieldMeta {
+                name: "SkippedFieldStruct".to_string(),
+                doc: "This is a struct with a skipped field".to_string(),
+                ..FieldMeta::default()
+            },
             type_name: "SkippedFieldStruct".to_string(),
+            path: "tests::SkippedFieldStruct".to_string(),
+            type_doc: "This is a struct with a skipped field".to_string(),
             fields: vec![Field::Primitive(Primitive {
-                name: "not_skipped".to_string(),
+                meta: FieldMeta {
+                    name: "not_skipped".to_string(),
+                    doc: "This field is not skipped".to_string(),
+                    ..FieldMeta::default()
+                },
                 typ: Typ::String,
-                doc: "This field is not skipped".to_string(),
+                type_name: None,
             })],
-            doc: "This is a struct with a skipped field".to_string(),
+            deny_unknown_fields: false,
         });
 
         assert_eq!(SkippedFieldStruct::dox(), expected);